@@ -0,0 +1,487 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_std::channel::unbounded;
+use ethers::providers::{Middleware, Provider};
+use ethers::types::Address;
+use reqwest::Client;
+
+use crate::clock::{Clock, SystemClock};
+use crate::core::{parse_contracts, Configuration, ErrorBudget, FeedKey, Reflector, RetryBudget, RpcAggregation, Rustlink, SmoothingConfig};
+use crate::error::Error;
+use crate::transport::RpcHttp;
+
+/// ## Rustlink builder
+/// Collects the configuration of a Rustlink instance, starting from sane defaults:
+/// a fetch interval of 60 seconds, no contracts and every optional behaviour disabled.
+/// Only the RPC url and the reflector have no default, they are passed to `build()`.
+///
+/// ```rust
+/// use async_std::channel::unbounded;
+/// use rustlink::core::{Reflector, RustlinkBuilder};
+///
+/// let (sender, receiver) = unbounded();
+///
+/// let rustlink = RustlinkBuilder::default()
+///     .with_fetch_interval_seconds(5)
+///     .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+///     .build("https://bsc-dataseed1.binance.org/", Reflector::Sender(sender))
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RustlinkBuilder {
+    fetch_interval_seconds: u64,
+    contracts: Vec<(String, String)>,
+    chain_id: Option<u64>,
+    stagger_start: bool,
+    tick_events: bool,
+    batch_window: Option<Duration>,
+    dedup: bool,
+    smoothing: Option<SmoothingConfig>,
+    max_rounds: Option<u64>,
+    max_runtime: Option<Duration>,
+    emit_on_start: bool,
+    sanity_ranges: HashMap<FeedKey, (f64, f64)>,
+    exact_answer: bool,
+    rpc_aggregation: Option<RpcAggregation>,
+    aggregation_rpc_urls: Vec<String>,
+    fallback_rpc_urls: Vec<String>,
+    call_from: Option<Address>,
+    strict_checksum: bool,
+    error_budget: Option<ErrorBudget>,
+    phase_offset: Duration,
+    clock: Arc<dyn Clock>,
+    conditional_fetch: bool,
+    quotes: HashMap<FeedKey, String>,
+    quotes_from_description: bool,
+    freeze_threshold: Option<Duration>,
+    decimals_overrides: HashMap<FeedKey, u8>,
+    align_to_interval: bool,
+    call_timeout_seconds: u64,
+    max_retries: u32,
+    retry_base_delay_ms: u64,
+    retry_budget: Option<RetryBudget>,
+    decimals_check_ticks: Option<u32>,
+    request_ids: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_max_idle_per_host: Option<usize>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_idle_timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    tcp_keepalive: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    compression: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    user_agent: Option<String>,
+}
+
+impl Default for RustlinkBuilder {
+    fn default() -> Self {
+        RustlinkBuilder {
+            fetch_interval_seconds: 60,
+            contracts: Vec::new(),
+            chain_id: None,
+            stagger_start: false,
+            tick_events: false,
+            batch_window: None,
+            dedup: false,
+            smoothing: None,
+            max_rounds: None,
+            max_runtime: None,
+            emit_on_start: false,
+            sanity_ranges: HashMap::new(),
+            exact_answer: false,
+            rpc_aggregation: None,
+            aggregation_rpc_urls: Vec::new(),
+            fallback_rpc_urls: Vec::new(),
+            call_from: None,
+            strict_checksum: false,
+            error_budget: None,
+            phase_offset: Duration::ZERO,
+            clock: Arc::new(SystemClock),
+            conditional_fetch: false,
+            quotes: HashMap::new(),
+            quotes_from_description: false,
+            freeze_threshold: None,
+            decimals_overrides: HashMap::new(),
+            align_to_interval: false,
+            call_timeout_seconds: 30,
+            max_retries: 0,
+            retry_base_delay_ms: 500,
+            retry_budget: None,
+            decimals_check_ticks: None,
+            request_ids: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_max_idle_per_host: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            pool_idle_timeout: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            tcp_keepalive: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            compression: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            user_agent: None,
+        }
+    }
+}
+
+impl RustlinkBuilder {
+    /// How often to update data points (to prevent RPC rate limitation)
+    pub fn with_fetch_interval_seconds(mut self, fetch_interval_seconds: u64) -> Self {
+        self.fetch_interval_seconds = fetch_interval_seconds;
+        self
+    }
+
+    /// Adds a contract, identified by a ticker name, to fetch rounds from
+    pub fn with_contract(mut self, identifier: &str, address: &str) -> Self {
+        self.contracts.push((identifier.to_string(), address.to_string()));
+        self
+    }
+
+    /// Adds a list of ticker names and their corresponding contract addresses
+    pub fn with_contracts(mut self, contracts: Vec<(String, String)>) -> Self {
+        self.contracts.extend(contracts);
+        self
+    }
+
+    /// The chain id to stamp on rounds, instead of querying it via `eth_chainId`
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Delays the first fetch by a random duration within the interval
+    pub fn with_stagger_start(mut self, stagger_start: bool) -> Self {
+        self.stagger_start = stagger_start;
+        self
+    }
+
+    /// Emits a `FetchEvent::Tick` after every pass over the contracts
+    pub fn with_tick_events(mut self, tick_events: bool) -> Self {
+        self.tick_events = tick_events;
+        self
+    }
+
+    /// How long a `Reflector::BatchSender` coalesces rounds before emitting them
+    pub fn with_batch_window(mut self, batch_window: Duration) -> Self {
+        self.batch_window = Some(batch_window);
+        self
+    }
+
+    /// Suppresses rounds whose id was already emitted for the same feed
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    /// Computes `Round::smoothed_answer` for every feed
+    pub fn with_smoothing(mut self, smoothing: SmoothingConfig) -> Self {
+        self.smoothing = Some(smoothing);
+        self
+    }
+
+    /// Stops fetching on its own after emitting this many rounds
+    pub fn with_max_rounds(mut self, max_rounds: u64) -> Self {
+        self.max_rounds = Some(max_rounds);
+        self
+    }
+
+    /// Stops fetching on its own once it has been running this long
+    pub fn with_max_runtime(mut self, max_runtime: Duration) -> Self {
+        self.max_runtime = Some(max_runtime);
+        self
+    }
+
+    /// Fetches every feed right away when starting instead of waiting a full interval
+    pub fn with_emit_on_start(mut self, emit_on_start: bool) -> Self {
+        self.emit_on_start = emit_on_start;
+        self
+    }
+
+    /// Withholds rounds of the feed of the primary chain whose answer lies outside of `min..=max`
+    pub fn with_sanity_range(mut self, identifier: &str, min: f64, max: f64) -> Self {
+        self.sanity_ranges.insert((0, identifier.to_string()), (min, max));
+        self
+    }
+
+    /// Fills in `Round::exact_answer` on every round
+    pub fn with_exact_answer(mut self, exact_answer: bool) -> Self {
+        self.exact_answer = exact_answer;
+        self
+    }
+
+    /// Fetches every feed of the primary chain from each of these RPCs as well, combining
+    /// the answers into one round. The urls are only used by `build`, when using
+    /// `build_with_provider` push the providers to `Configuration::aggregation_providers`.
+    pub fn with_rpc_aggregation(mut self, rpc_aggregation: RpcAggregation, rpc_urls: &[&str]) -> Self {
+        self.rpc_aggregation = Some(rpc_aggregation);
+        self.aggregation_rpc_urls = rpc_urls.iter().map(|rpc_url| rpc_url.to_string()).collect();
+        self
+    }
+
+    /// Fails over to these RPCs of the primary chain, in order, when the active one cannot be
+    /// reached, staying on the new one until it fails in turn. The urls are only used by `build`,
+    /// when using `build_with_provider` push the providers to `Configuration::fallback_providers`.
+    pub fn with_fallback_rpc_urls(mut self, rpc_urls: &[&str]) -> Self {
+        self.fallback_rpc_urls = rpc_urls.iter().map(|rpc_url| rpc_url.to_string()).collect();
+        self
+    }
+
+    /// Reads the rounds with `eth_call`s made from this address, for access controlled feeds
+    pub fn with_call_from(mut self, call_from: Address) -> Self {
+        self.call_from = Some(call_from);
+        self
+    }
+
+    /// Rejects mixed case contract addresses whose EIP-55 checksum is invalid
+    pub fn with_strict_checksum(mut self, strict_checksum: bool) -> Self {
+        self.strict_checksum = strict_checksum;
+        self
+    }
+
+    /// Gives up once every fetch failed for a number of consecutive passes, see `ErrorBudget`
+    pub fn with_error_budget(mut self, error_budget: ErrorBudget) -> Self {
+        self.error_budget = Some(error_budget);
+        self
+    }
+
+    /// Delays the first fetch by this fixed duration, to phase shift replicas
+    pub fn with_phase_offset(mut self, phase_offset: Duration) -> Self {
+        self.phase_offset = phase_offset;
+        self
+    }
+
+    /// Replaces the system clock timestamping feed statuses and tick events, e.g. with a `MockClock` in tests
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Reads the cheap `latestTimestamp` of a feed first and only fetches the whole round when it changed. Feeds without `latestTimestamp` are always fetched in full.
+    pub fn with_conditional_fetch(mut self, conditional_fetch: bool) -> Self {
+        self.conditional_fetch = conditional_fetch;
+        self
+    }
+
+    /// Labels the answers of the feed of the primary chain with their quote currency, e.g. `USD`
+    pub fn with_quote(mut self, identifier: &str, quote: &str) -> Self {
+        self.quotes.insert((0, identifier.to_string()), quote.to_string());
+        self
+    }
+
+    /// Takes `Round::quote` from the description of the aggregator, e.g. `USD` for `ETH / USD`, for feeds without a configured quote
+    pub fn with_quotes_from_description(mut self, quotes_from_description: bool) -> Self {
+        self.quotes_from_description = quotes_from_description;
+        self
+    }
+
+    /// Emits `FetchEvent::FeedFrozen` when the latest round of a feed was updated longer than `freeze_threshold` ago, even though fetching it succeeds
+    pub fn with_freeze_threshold(mut self, freeze_threshold: Duration) -> Self {
+        self.freeze_threshold = Some(freeze_threshold);
+        self
+    }
+
+    /// Uses `decimals` for the answers of the feed of the primary chain instead of querying its aggregator
+    pub fn with_decimals_override(mut self, identifier: &str, decimals: u8) -> Self {
+        self.decimals_overrides.insert((0, identifier.to_string()), decimals);
+        self
+    }
+
+    /// Uses the given decimals per identifier of the primary chain instead of querying the aggregators, e.g. as loaded
+    /// from a configuration file. Adds to the overrides configured so far.
+    pub fn with_decimals_overrides(mut self, decimals_overrides: HashMap<String, u8>) -> Self {
+        self.decimals_overrides.extend(decimals_overrides.into_iter().map(|(identifier, decimals)| ((0, identifier), decimals)));
+        self
+    }
+
+    /// Delays the first fetch until the next multiple of the fetch interval since the unix epoch, e.g. :00, :05, :10 with a 5 second interval
+    pub fn with_align_to_interval(mut self, align_to_interval: bool) -> Self {
+        self.align_to_interval = align_to_interval;
+        self
+    }
+
+    /// Fails contract calls that take longer than `call_timeout_seconds` with `Error::Timeout`, so a hanging RPC endpoint cannot stall fetching
+    pub fn with_call_timeout_seconds(mut self, call_timeout_seconds: u64) -> Self {
+        self.call_timeout_seconds = call_timeout_seconds;
+        self
+    }
+
+    /// Retries a failed fetch of a feed up to `max_retries` times within the same pass, so a briefly rate limited RPC does not cost a data point
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// How long to wait before the first retry of a failed fetch, doubling with every further attempt
+    pub fn with_retry_base_delay_ms(mut self, retry_base_delay_ms: u64) -> Self {
+        self.retry_base_delay_ms = retry_base_delay_ms;
+        self
+    }
+
+    /// Limits the retries of all feeds together, so that an outage of the RPC does not turn into a retry storm, see `RetryBudget`
+    pub fn with_retry_budget(mut self, retry_budget: RetryBudget) -> Self {
+        self.retry_budget = Some(retry_budget);
+        self
+    }
+
+    /// Queries the decimals of every feed again every `decimals_check_ticks` passes, reconnecting feeds whose aggregator now reports a different number, see `FetchEvent::DecimalsChanged`
+    pub fn with_decimals_check_ticks(mut self, decimals_check_ticks: u32) -> Self {
+        self.decimals_check_ticks = Some(decimals_check_ticks);
+        self
+    }
+
+    /// Maximum number of idle HTTP connections kept open to the RPC
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_pool_max_idle_per_host(mut self, pool_max_idle_per_host: usize) -> Self {
+        self.pool_max_idle_per_host = Some(pool_max_idle_per_host);
+        self
+    }
+
+    /// How long an idle HTTP connection to the RPC is kept open
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_pool_idle_timeout(mut self, pool_idle_timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(pool_idle_timeout);
+        self
+    }
+
+    /// Interval of the TCP keepalive probes sent on connections to the RPC
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_tcp_keepalive(mut self, tcp_keepalive: Duration) -> Self {
+        self.tcp_keepalive = Some(tcp_keepalive);
+        self
+    }
+
+    /// Asks the RPC for gzip or brotli compressed responses and decompresses them transparently.
+    /// In the browser compression is always negotiated by the browser itself.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_compression(mut self, compression: bool) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// User-Agent sent with every request to the RPC, making the traffic identifiable
+    /// in the dashboards of the RPC provider. Browsers always send their own.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Sends every request to the RPC with an `X-Request-Id` header holding its JSON-RPC id, so that
+    /// the provider can find a request in its logs. In the browser the header makes the requests
+    /// subject to a CORS preflight, which the RPC has to allow.
+    pub fn with_request_ids(mut self, request_ids: bool) -> Self {
+        self.request_ids = request_ids;
+        self
+    }
+
+    /// Creates the Rustlink instance.
+    ///
+    /// Expected parameters:
+    /// - `rpc_url`: The RPC url of your chosen EVM network where Chainlink offers decentralised data feeds.
+    /// - `reflector`: How you choose to receive the answer from your provided contracts.
+    pub fn build(self, rpc_url: &str, reflector: Reflector) -> Result<Rustlink<Provider<RpcHttp>>, Error> {
+        let transport = RpcHttp::parse(rpc_url, self.http_client()?, self.request_ids)?;
+        let aggregation_providers = self
+            .aggregation_rpc_urls
+            .iter()
+            .map(|rpc_url| transport.with_url(rpc_url).map(Provider::new))
+            .collect::<Result<_, _>>()?;
+        let fallback_providers = self
+            .fallback_rpc_urls
+            .iter()
+            .map(|rpc_url| transport.with_url(rpc_url).map(Provider::new))
+            .collect::<Result<_, _>>()?;
+
+        let mut rustlink = self.build_with_provider(Provider::new(transport), reflector)?;
+        rustlink.configuration.aggregation_providers = aggregation_providers;
+        rustlink.configuration.fallback_providers = fallback_providers;
+        Ok(rustlink)
+    }
+
+    /// Builds the HTTP client used to reach the RPC.
+    fn http_client(&self) -> Result<Client, Error> {
+        #[allow(unused_mut)]
+        let mut client = Client::builder();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(pool_max_idle_per_host) = self.pool_max_idle_per_host {
+                client = client.pool_max_idle_per_host(pool_max_idle_per_host);
+            }
+            if let Some(pool_idle_timeout) = self.pool_idle_timeout {
+                client = client.pool_idle_timeout(pool_idle_timeout);
+            }
+            if let Some(tcp_keepalive) = self.tcp_keepalive {
+                client = client.tcp_keepalive(tcp_keepalive);
+            }
+            client = client.gzip(self.compression).brotli(self.compression);
+            if let Some(user_agent) = &self.user_agent {
+                client = client.user_agent(user_agent);
+            }
+        }
+
+        client
+            .build()
+            .map_err(|error| Error::HttpClient(error.to_string()))
+    }
+
+    /// Creates the Rustlink instance on top of an existing provider, using any transport
+    /// such as IPC or WebSockets, optionally wrapped in custom middleware.
+    pub fn build_with_provider<M: Middleware>(
+        self,
+        provider: M,
+        reflector: Reflector,
+    ) -> Result<Rustlink<M>, Error> {
+        let (termination_send, termination_recv) = unbounded::<()>();
+        let (shutdown_send, shutdown_recv) = unbounded::<()>();
+
+        Ok(Rustlink {
+            configuration: Configuration {
+                fetch_interval_seconds: self.fetch_interval_seconds,
+                provider,
+                contracts: parse_contracts(self.contracts, self.strict_checksum)?,
+                chain_id: self.chain_id,
+                chains: Vec::new(),
+                stagger_start: self.stagger_start,
+                tick_events: self.tick_events,
+                batch_window: self.batch_window,
+                dedup: self.dedup,
+                smoothing: self.smoothing,
+                max_rounds: self.max_rounds,
+                max_runtime: self.max_runtime,
+                emit_on_start: self.emit_on_start,
+                sanity_ranges: self.sanity_ranges,
+                exact_answer: self.exact_answer,
+                rpc_aggregation: self.rpc_aggregation,
+                aggregation_providers: Vec::new(),
+                fallback_providers: Vec::new(),
+                call_from: self.call_from,
+                strict_checksum: self.strict_checksum,
+                error_budget: self.error_budget,
+                phase_offset: self.phase_offset,
+                clock: self.clock,
+                conditional_fetch: self.conditional_fetch,
+                quotes: self.quotes,
+                quotes_from_description: self.quotes_from_description,
+                freeze_threshold: self.freeze_threshold,
+                decimals_overrides: self.decimals_overrides,
+                align_to_interval: self.align_to_interval,
+                call_timeout_seconds: self.call_timeout_seconds,
+                max_retries: self.max_retries,
+                retry_base_delay_ms: self.retry_base_delay_ms,
+                retry_budget: self.retry_budget,
+                decimals_check_ticks: self.decimals_check_ticks,
+            },
+            reflector,
+            termination_send,
+            termination_recv,
+            shutdown_send,
+            shutdown_recv,
+            feed_status: Default::default(),
+            seq: Default::default(),
+            subscribers: Default::default(),
+            running: Default::default(),
+            active_provider: Default::default(),
+        })
+    }
+}
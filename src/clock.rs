@@ -0,0 +1,52 @@
+//! Sources of the wall-clock time rustlink stamps on feed statuses and events.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Tells the current time as a unix timestamp in milliseconds.
+/// Configure one with `RustlinkBuilder::with_clock`, `SystemClock` being the default.
+pub trait Clock: Debug + Send + Sync {
+    fn now_millis(&self) -> u64;
+}
+
+/// The system's wall clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        workflow_rs::core::time::unixtime_as_millis_u64()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic tests of time dependent behaviour.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: AtomicU64,
+}
+
+impl MockClock {
+    /// Creates a clock standing still at the given unix timestamp in milliseconds.
+    pub fn new(now_millis: u64) -> Self {
+        MockClock {
+            now: AtomicU64::new(now_millis),
+        }
+    }
+
+    /// Moves the clock to the given unix timestamp in milliseconds.
+    pub fn set(&self, now_millis: u64) {
+        self.now.store(now_millis, Ordering::SeqCst);
+    }
+
+    /// Moves the clock forward.
+    pub fn advance(&self, duration: Duration) {
+        self.now.fetch_add(duration.as_millis() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_millis(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,8 @@
+//! Deprecated home of the Rustlink types, kept so that code importing from
+//! `rustlink::config` keeps compiling. Everything here is a re-export of `rustlink::core`,
+//! so values from either path are the same types and need no conversion.
+//!
+//! To migrate, replace `rustlink::config::` with `rustlink::core::` in your imports.
+//! This module will be removed in a future release.
+
+pub use crate::core::{Reflector, Rustlink};
@@ -1,26 +1,143 @@
-use crate::{error::Error, fetcher::fetch_rounds, interface};
+use crate::clock::Clock;
+use crate::transport::RpcHttp;
+use crate::{error::Error, fetcher::{fetch_round_data_for_contract, fetch_rounds, is_unreachable}, interface};
 
-use async_std::channel::{unbounded, Receiver, RecvError, Sender};
-use ethers::{providers::{Http, Provider}, types::Address};
-use js_sys::Function;
+use async_std::channel::{Receiver, RecvError, Sender};
+use async_trait::async_trait;
+#[cfg(not(target_arch = "wasm32"))]
+use ethers::providers::Ipc;
+use ethers::{providers::{Middleware, Provider}, types::Address, utils::to_checksum};
+use futures::future::{self, join_all, Either, Shared};
+use futures::{stream, Future, FutureExt, Stream, StreamExt};
+use js_sys::{Function, Promise};
+use serde_json::json;
 use serde_wasm_bindgen::{from_value, to_value};
 use workflow_rs::core::cfg_if;
-use std::str::FromStr;
-use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
-use wasm_bindgen_futures::spawn_local;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}, Arc, Mutex},
+    time::Duration,
+};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
 
 /// ## Configuration
 /// This struct contains the configuration for Rustlink. It contains the following fields:
 /// - `fetch_interval_seconds`: How often to update data points (to prevent RPC rate limitation)
-/// - `contracts`: A list of tuples containing a ticker name and its corresponding contract address on the EVM chain
-/// - `provider`: The provider to use for fetching data
+/// - `contracts`: A list of tuples containing a ticker name and its corresponding contract address on the EVM chain.
+///   Feeds are fetched one at a time in this order, followed by the contracts of `chains`, so rounds
+///   are always emitted in configuration order
+/// - `provider`: The provider to use for fetching data. Any middleware over any transport can be used,
+///   an HTTP `Provider` by default
+/// - `chain_id`: The id of the provider's chain, stamped on every `Round`. When `None` it is queried
+///   once via `eth_chainId` when fetching starts
+/// - `chains`: Additional chains whose contracts are fetched in the same loop
+/// - `stagger_start`: Whether to delay the first fetch by a random duration within the interval,
+///   spreading the RPC load of many instances started at the same time
+/// - `tick_events`: Whether to emit a `FetchEvent::Tick` after every pass over the contracts
+/// - `batch_window`: How long a `Reflector::BatchSender` coalesces rounds before emitting them at once.
+///   When `None` the rounds of each pass over the contracts are emitted together
+/// - `dedup`: Whether to suppress rounds whose id was already emitted for the same feed
+///   during the lifetime of this instance
+/// - `smoothing`: How to compute `Round::smoothed_answer` for every feed, if at all
+/// - `max_rounds`: Stop on our own after emitting this many rounds
+/// - `max_runtime`: Stop on our own once fetching has been running this long
+/// - `emit_on_start`: Whether to fetch every feed right away when starting, instead of waiting
+///   for the first interval to elapse
+/// - `sanity_ranges`: Inclusive `(min, max)` bounds of the answer per feed, see `FeedKey`. Rounds answering outside
+///   of them are withheld and reported as `FetchEvent::OutlierRejected`
+/// - `exact_answer`: Whether to fill in `Round::exact_answer`, the answer as a string with exactly
+///   `decimals` fractional digits, for consumers that cannot represent it as a float
+/// - `rpc_aggregation`: How to combine the answers of the primary chain fetched from `provider` and every
+///   provider of `aggregation_providers` into one round
+/// - `aggregation_providers`: Additional providers of the primary chain, only queried when `rpc_aggregation` is set
+/// - `fallback_providers`: Providers of the primary chain to fail over to, in order, when the active one cannot
+///   be reached. Not used when `rpc_aggregation` is set
+/// - `call_from`: Address the `eth_call`s reading the rounds are made from, for access controlled
+///   feeds that revert for unknown callers
+/// - `strict_checksum`: Whether to reject mixed case contract addresses that are not valid EIP-55
+///   checksums, catching typos. All lowercase or uppercase addresses are always accepted
+/// - `error_budget`: How many consecutive passes over the contracts may fail entirely before giving up,
+///   see `ErrorBudget`
+/// - `phase_offset`: Fixed delay before the first fetch, to phase shift several instances polling the
+///   same feeds. Applied before the random delay of `stagger_start`, if any
+/// - `clock`: Source of the timestamps of `FeedStatus` and `FetchEvent::Tick`, the system clock
+///   by default
+/// - `conditional_fetch`: Whether each fetch first reads `latestTimestamp` and only fetches the whole round
+///   when the feed was updated since the last fetch.
+/// - `quotes`: The quote currency of the answer per feed, see `FeedKey`, stamped on rounds as `Round::quote`.
+/// - `quotes_from_description`: Whether feeds without a configured quote take it from the description of their aggregator,
+///   e.g. `USD` for `ETH / USD`. The description is queried once per feed.
+/// - `freeze_threshold`: How long the `updated_at` of a feed may lag behind the clock before `FetchEvent::FeedFrozen`
+///   is emitted, once per stalled round. `None` disables the check.
+/// - `decimals_overrides`: Decimals per feed, see `FeedKey`, used instead of querying `decimals()` on chain, for feeds
+///   reporting a wrong value. Feeds without an override query their aggregator.
+/// - `align_to_interval`: Whether to delay the first fetch until the clock reaches a multiple of the fetch interval since
+///   the unix epoch, so that instances started independently fetch at the same moments.
+/// - `call_timeout_seconds`: How long each contract call may take before failing with `Error::Timeout`
+/// - `max_retries`: How many times a failed fetch of a feed is retried within a pass, waiting `retry_base_delay_ms` doubled
+///   after every attempt. Only connection failures, timeouts and rate limits are retried, including those
+///   of connecting to the contract.
+/// - `retry_base_delay_ms`: How long to wait before the first retry of a failed fetch
+/// - `retry_budget`: How many retries all feeds together may make per unit of time, see `RetryBudget`. Failures are
+///   not retried while it is exhausted. `None` leaves retries limited by `max_retries` only.
+/// - `decimals_check_ticks`: Every how many passes over the contracts the decimals of cached contracts are queried again, to notice
+///   aggregator upgrades changing them. A change emits `FetchEvent::DecimalsChanged` and reconnects the feed.
+///   `None` queries them only when connecting. Feeds with `decimals_overrides` are not checked.
 #[derive(Clone)]
-pub struct Configuration {
+pub struct Configuration<M: Middleware = Provider<RpcHttp>> {
     pub fetch_interval_seconds: u64,
     pub contracts: Vec<(String, Address)>,
-    pub provider: Provider<Http>,
+    pub provider: M,
+    pub chain_id: Option<u64>,
+    pub chains: Vec<Chain<M>>,
+    pub stagger_start: bool,
+    pub tick_events: bool,
+    pub batch_window: Option<Duration>,
+    pub dedup: bool,
+    pub smoothing: Option<SmoothingConfig>,
+    pub max_rounds: Option<u64>,
+    pub max_runtime: Option<Duration>,
+    pub emit_on_start: bool,
+    pub sanity_ranges: HashMap<FeedKey, (f64, f64)>,
+    pub exact_answer: bool,
+    pub rpc_aggregation: Option<RpcAggregation>,
+    pub aggregation_providers: Vec<M>,
+    pub fallback_providers: Vec<M>,
+    pub call_from: Option<Address>,
+    pub strict_checksum: bool,
+    pub error_budget: Option<ErrorBudget>,
+    pub phase_offset: Duration,
+    pub clock: Arc<dyn Clock>,
+    pub conditional_fetch: bool,
+    pub quotes: HashMap<FeedKey, String>,
+    pub quotes_from_description: bool,
+    pub freeze_threshold: Option<Duration>,
+    pub decimals_overrides: HashMap<FeedKey, u8>,
+    pub align_to_interval: bool,
+    pub call_timeout_seconds: u64,
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_budget: Option<RetryBudget>,
+    pub decimals_check_ticks: Option<u32>,
 }
 
+/// ## Chain
+/// A group of contracts living on another EVM chain, fetched through its own provider.
+/// Rounds from every chain are multiplexed into the same reflector, stamped with their chain id.
+#[derive(Clone)]
+pub struct Chain<M: Middleware = Provider<RpcHttp>> {
+    pub provider: M,
+    pub contracts: Vec<(String, Address)>,
+    pub chain_id: Option<u64>,
+}
+
+/// Identifies a feed among those of every chain: the index of its chain, `0` being the primary
+/// chain followed by `Configuration::chains` in order, and its identifier. The same identifier
+/// may be used on several chains.
+pub type FeedKey = (usize, String);
+
 /// ## Rustlink instance. This is the main struct that you will interact with.
 ///
 /// Rustlink is a lightweight Rust library that provides your Rust applications with a direct
@@ -28,13 +145,44 @@ pub struct Configuration {
 /// price feeds. Just copy the contract addresses for the symbol that you would like to track from:
 ///
 #[derive(Clone)]
-pub struct Rustlink {
-    pub configuration: Configuration,
+pub struct Rustlink<M: Middleware = Provider<RpcHttp>> {
+    pub configuration: Configuration<M>,
     pub reflector: Reflector,
     pub termination_send: Sender<()>,
     pub termination_recv: Receiver<()>,
     pub shutdown_send: Sender<()>,
     pub shutdown_recv: Receiver<()>,
+    pub(crate) feed_status: Arc<Mutex<HashMap<FeedKey, FeedStatus>>>,
+    pub(crate) seq: Arc<AtomicU64>,
+    pub(crate) running: Arc<AtomicBool>,
+    /// Index of the provider of the primary chain in use, `0` being `provider` and the others
+    /// following `fallback_providers`
+    pub(crate) active_provider: Arc<AtomicUsize>,
+    pub(crate) subscribers: Arc<Mutex<HashMap<FeedKey, Vec<Sender<Round>>>>>,
+}
+
+/// ## Feed info
+/// A configured feed along with the metadata its aggregator reports, as returned by
+/// [`Rustlink::feeds`]. The description is `None` when the aggregator could not provide one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedInfo {
+    pub identifier: String,
+    pub address: Address,
+    pub decimals: u8,
+    pub description: Option<String>,
+}
+
+/// ## Feed status
+/// The health of a single feed, as observed by the fetch loop. Timestamps are unix
+/// timestamps in milliseconds, `None` until the first success or error.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeedStatus {
+    /// Index of the chain of the feed, see `FeedKey`
+    pub chain_index: usize,
+    pub identifier: String,
+    pub last_success: Option<u64>,
+    pub last_error: Option<u64>,
+    pub consecutive_failures: u32,
 }
 
 /// Rustlink allows you as a developer to retrieve the sought Chainlink data in
@@ -57,19 +205,155 @@ pub struct Rustlink {
 pub enum Reflector {
     /// A sender from async-std
     Sender(Sender<Round>),
+    /// A sender from async-std receiving rounds as well as the other events of the fetch loop
+    Events(Sender<FetchEvent>),
+    /// A sender from async-std receiving rounds in batches, see `Configuration::batch_window`
+    BatchSender(Sender<Vec<Round>>),
+    /// A user defined sink, such as a database writer or a message queue producer
+    Custom(Arc<dyn RoundSink>),
+    /// Prints every round as a line of JSON to stdout, or to the console on WASM. Meant for
+    /// debugging and smoke tests.
+    Stdout,
+    /// A closure called with every round, the native counterpart of the callback of `RustlinkJS`.
+    /// It is called from the fetch loop, so it should return quickly.
+    Callback(Arc<dyn Fn(Round) + Send + Sync>),
+    /// A tokio broadcast sender, every receiver subscribed to it gets a clone of every round.
+    /// The channel keeps the last `capacity` rounds: a receiver falling further behind skips
+    /// the oldest ones, its next `recv` failing with `RecvError::Lagged` telling how many.
+    /// Rounds sent while nobody is subscribed are dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    Broadcast(tokio::sync::broadcast::Sender<Round>),
+}
+
+impl Reflector {
+    /// Whether the round channel was closed, by the end of a previous run or because every
+    /// receiver was dropped, so that no round can be delivered anymore.
+    pub(crate) fn is_closed(&self) -> bool {
+        match self {
+            Reflector::Sender(sender) => sender.is_closed(),
+            Reflector::BatchSender(sender) => sender.is_closed(),
+            _ => false,
+        }
+    }
+}
+
+/// ## Round sink
+/// A destination for rounds implemented outside of this crate, used through `Reflector::Custom`.
+/// The fetch loop awaits `emit` before fetching the next feed, so slow sinks should hand
+/// the round off rather than doing heavy work inline.
+///
+/// ```rust
+/// use rustlink::core::{Round, RoundSink};
+///
+/// struct Printer;
+///
+/// #[async_trait::async_trait]
+/// impl RoundSink for Printer {
+///     async fn emit(&self, round: Round) {
+///         println!("{}: {}", round.identifier, round.answer);
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait RoundSink: Send + Sync {
+    async fn emit(&self, round: Round);
 }
 
 pub type Round = interface::Round;
 
+pub use crate::builder::RustlinkBuilder;
+pub use crate::fetcher::replay::ReplaySource;
+
+/// How the answers of a feed are smoothed into `Round::smoothed_answer`.
+/// The raw answer of every round is left untouched.
+#[derive(Clone, Copy, Debug)]
+pub enum SmoothingConfig {
+    /// The average of the last `n` answers
+    MovingAverage(usize),
+    /// An exponential moving average, weighting each new answer by the given factor in `(0, 1]`
+    Exponential(f64),
+}
+
+/// How the answers of one feed fetched from several RPCs are combined into one round.
+/// Providers failing to answer are left out. The combined round carries the metadata,
+/// such as `round_id` and `updated_at`, of the oldest answer.
+#[derive(Clone, Copy, Debug)]
+pub enum RpcAggregation {
+    /// The middle answer, or the mean of the two middle answers
+    Median,
+    /// The mean of all answers
+    Mean,
+}
+
+/// Limits how long the fetch loop keeps polling an RPC that looks dead.
+/// When every fetch of `max_failed_passes` consecutive passes over the contracts failed,
+/// the loop emits `FetchEvent::GivingUp` and stops, or pauses for `cooldown` if set.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorBudget {
+    pub max_failed_passes: u32,
+    pub cooldown: Option<Duration>,
+}
+
+/// A token bucket of `retries` retries, refilled at `retries` per `per`, shared by the retries
+/// of every feed. Starts full.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryBudget {
+    pub retries: u32,
+    pub per: Duration,
+}
+
+/// Everything the fetch loop can report through `Reflector::Events`.
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)] // Rounds are the common case, boxing them buys nothing
+pub enum FetchEvent {
+    /// A round was fetched from one of the contracts
+    Round(Round),
+    /// The loop finished a pass over all contracts, regardless of whether the fetches succeeded.
+    /// `at` is a unix timestamp in milliseconds. Only emitted when `tick_events` is enabled.
+    Tick { at: u64 },
+    /// Every fetch failed for as many consecutive passes as the error budget allows. The loop
+    /// stops afterwards, or pauses for the cooldown of the budget if it has one.
+    GivingUp { failed_passes: u32 },
+    /// A round was withheld because its answer is outside of the sanity range of its feed
+    OutlierRejected(Round),
+    /// A round was withheld because it was answered in an earlier round than its own,
+    /// which Chainlink recommends treating as stale
+    StaleRound {
+        identifier: String,
+        round_id: u128,
+        answered_in_round: u128,
+    },
+    /// The latest round of a feed was updated longer ago than the `freeze_threshold`, so the
+    /// oracle stopped updating even though fetching it succeeds. `last_update` is the unix
+    /// timestamp in seconds of that round. Emitted once per stalled round.
+    FeedFrozen {
+        identifier: String,
+        last_update: u64,
+        since: Duration,
+    },
+    /// The aggregator of a feed now reports its answers in `new` rather than `old` decimals, as
+    /// noticed by the periodic check of `decimals_check_ticks`. The feed is reconnected, so its
+    /// rounds are scaled by the new decimals from the following fetch on.
+    DecimalsChanged {
+        identifier: String,
+        old: u8,
+        new: u8,
+    },
+    /// The loop has ended, because it was stopped or stopped on its own. Always the last
+    /// event of a run.
+    Closed,
+}
+
 impl Rustlink {
-    /// Creates a new Rustlink instance.
+    /// Creates a new Rustlink instance. Use `RustlinkBuilder` to configure more than the basics.
     ///
     /// Expected parameters:
-    /// - `rpc_url`: The RPC url of your chosen EVM network where Chainlink offers decentralised data feeds.
+    /// - `rpc_urls`: The RPC urls of your chosen EVM network where Chainlink offers decentralised data feeds.
+    ///   The first one is used until it cannot be reached, then the next one takes over, and so on.
     /// - `fetch_interval_seconds`: How often to update data points in the database (to prevent RPC rate limitation)
     /// - `reflector`: How you choose to receive the answer from your provided contracts.
     /// - `contracts`: A tuple list containing a ticker name and its corresponding contract address on the
-    /// EVM chain.
+    ///   EVM chain.
     ///
     /// Example:
     ///
@@ -89,64 +373,608 @@ impl Rustlink {
     ///     let (sender, receiver) = unbounded();
     ///     
     ///     let rustlink = Rustlink::try_new(
-    ///         "https://bsc-dataseed1.binance.org/",
+    ///         vec![
+    ///             "https://bsc-dataseed1.binance.org/".to_string(),
+    ///             "https://bsc-dataseed2.binance.org/".to_string(),
+    ///         ],
     ///         1,
     ///         Reflector::Sender(sender),
     ///         contracts,
     ///     )
     ///     .unwrap();
-    ///     rustlink.start();
+    ///     rustlink.start().unwrap();
     ///     let round_data = receiver.recv().await.unwrap();
     ///     println!("Received data: {:#?}", round_data);
     /// }
     /// ```
     pub fn try_new(
+        rpc_urls: Vec<String>,
+        fetch_interval_seconds: u64,
+        reflector: Reflector,
+        contracts: Vec<(String, String)>,
+    ) -> Result<Self, Error> {
+        let (rpc_url, fallback_rpc_urls) = rpc_urls
+            .split_first()
+            .ok_or_else(|| Error::InvalidRpcUrl("none given".to_string()))?;
+        let fallback_rpc_urls: Vec<&str> = fallback_rpc_urls.iter().map(String::as_str).collect();
+
+        RustlinkBuilder::default()
+            .with_fetch_interval_seconds(fetch_interval_seconds)
+            .with_contracts(contracts)
+            .with_fallback_rpc_urls(&fallback_rpc_urls)
+            .build(rpc_url, reflector)
+    }
+
+    /// The RPC url of the primary chain that is fetched from, the first one given until it
+    /// cannot be reached and a fallback url took over.
+    pub fn active_rpc(&self) -> &str {
+        self.active_provider().as_ref().url().as_str()
+    }
+
+    /// Creates a `ReplaySource` that emits the given rounds, in order, through `reflector`
+    /// instead of fetching them from a chain. When `interval` is set the rounds are spaced
+    /// out by it. Handy for deterministic tests of the code consuming the rounds.
+    pub fn replay(rounds: Vec<Round>, interval: Option<Duration>, reflector: Reflector) -> ReplaySource {
+        ReplaySource::new(rounds, interval, reflector)
+    }
+
+    /// Adds the contracts of another EVM chain to this Rustlink instance.
+    /// They are fetched by the same loop through their own provider, and delivered
+    /// to the same reflector. Use `Round::chain_id` to tell the chains apart.
+    ///
+    /// The provider reuses the HTTP client of the primary chain, along with its settings such as
+    /// the user agent. Returns the index of the chain, which keys its feeds as a `FeedKey`.
+    ///
+    /// Expected parameters:
+    /// - `rpc_url`: The RPC url of the additional EVM network.
+    /// - `contracts`: A tuple list containing a ticker name and its corresponding contract address on
+    ///   that chain.
+    pub fn add_chain(
+        &mut self,
         rpc_url: &str,
+        contracts: Vec<(String, String)>,
+    ) -> Result<usize, Error> {
+        let transport = self.configuration.provider.as_ref().with_url(rpc_url)?;
+        self.add_chain_with_provider(Provider::new(transport), None, contracts)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Rustlink<Provider<Ipc>> {
+    /// Creates a new Rustlink instance fetching through the IPC socket of a local node,
+    /// avoiding the HTTP and TLS overhead of a co-located node.
+    ///
+    /// Expected parameters:
+    /// - `ipc_path`: The path of the node's IPC socket (or named pipe on Windows).
+    /// - `fetch_interval_seconds`: How often to update data points (to prevent RPC rate limitation)
+    /// - `reflector`: How you choose to receive the answer from your provided contracts.
+    /// - `contracts`: A tuple list containing a ticker name and its corresponding contract address on the
+    ///   EVM chain.
+    pub async fn try_new_ipc(
+        ipc_path: &str,
         fetch_interval_seconds: u64,
         reflector: Reflector,
         contracts: Vec<(String, String)>,
     ) -> Result<Self, Error> {
+        let ipc = Ipc::connect(ipc_path)
+            .await
+            .map_err(|error| Error::Connection(error.to_string()))?;
 
-        let provider = Provider::try_from(rpc_url).expect("Invalid RPC URL");
-        let (termination_send, termination_recv) = unbounded::<()>();
-        let (shutdown_send, shutdown_recv) = unbounded::<()>();
-
-        let parsed_contracts=contracts.iter().map(|(identifier, address)| {
-            (identifier.clone(), Address::from_str(address).expect("Invalid contract address specified"))
-        }).collect();
-
-        Ok(Rustlink {
-            configuration: Configuration {
-                fetch_interval_seconds,
-                provider,
-                contracts:parsed_contracts,
-            },
-            reflector,
-            termination_send,
-            termination_recv,
-            shutdown_send,
-            shutdown_recv,
-        })
+        RustlinkBuilder::default()
+            .with_fetch_interval_seconds(fetch_interval_seconds)
+            .with_contracts(contracts)
+            .build_with_provider(Provider::new(ipc), reflector)
     }
+}
 
+impl<M: Middleware + Clone + 'static> Rustlink<M> {
     /// Starts the Rustlink instance.
     /// This method will start fetching the latest price data from the Chainlink decentralized data feed. 
-    pub fn start(&self) {
+    /// Does nothing if the instance is already running. Fails with `Error::ReflectorClosed` when
+    /// the channel of a `Reflector::Sender` or `Reflector::BatchSender` is closed, as it is once
+    /// a previous run ended.
+    pub fn start(&self) -> Result<(), Error> {
+        if self.reflector.is_closed() {
+            return Err(Error::ReflectorClosed);
+        }
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        // Forget the signals of a previous run, they would end this one right away
+        while self.termination_recv.try_recv().is_ok() {}
+        while self.shutdown_recv.try_recv().is_ok() {}
+
         #[cfg(not(target_arch = "wasm32"))]
         tokio::task::spawn(fetch_rounds(self.clone()));
 
         #[cfg(target_arch = "wasm32")]
         async_std::task::block_on(fetch_rounds(self.clone()));
+        Ok(())
+    }
+
+    /// The provider of the primary chain, for making other calls over the same connection,
+    /// such as reading a related contract.
+    pub fn provider(&self) -> &M {
+        &self.configuration.provider
+    }
+
+    /// The provider of the primary chain that is fetched from, `provider` until it cannot be
+    /// reached and one of the fallback providers took over.
+    pub fn active_provider(&self) -> &M {
+        match self.active_provider.load(Ordering::SeqCst) {
+            0 => &self.configuration.provider,
+            index => &self.configuration.fallback_providers[index - 1],
+        }
+    }
+
+    /// Switches the primary chain over to the next of its providers, wrapping around to
+    /// `provider` after the last fallback. The new provider stays active until it fails.
+    pub(crate) fn fail_over(&self) {
+        let providers = self.configuration.fallback_providers.len() + 1;
+        let index = (self.active_provider.load(Ordering::SeqCst) + 1) % providers;
+        self.active_provider.store(index, Ordering::SeqCst);
+        log::warn!("Failing over to RPC provider {} of {}", index + 1, providers);
+    }
+
+    /// Whether the instance is fetching, from `start` until it is stopped or stops on its own.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Like `add_chain`, fetching the contracts of the chain through an existing provider.
+    /// `chain_id` is stamped on its rounds, or queried via `eth_chainId` when `None`.
+    pub fn add_chain_with_provider(
+        &mut self,
+        provider: M,
+        chain_id: Option<u64>,
+        contracts: Vec<(String, String)>,
+    ) -> Result<usize, Error> {
+        self.configuration.chains.push(Chain {
+            provider,
+            contracts: parse_contracts(contracts, self.configuration.strict_checksum)?,
+            chain_id,
+        });
+        Ok(self.configuration.chains.len())
+    }
+
+    /// Returns the status of every configured feed, in the order they were configured.
+    /// Feeds failing right now have a non-zero `consecutive_failures`.
+    pub fn feed_status(&self) -> Vec<FeedStatus> {
+        let feed_status = self.feed_status.lock().unwrap();
+        let configuration = &self.configuration;
+
+        std::iter::once(&configuration.contracts)
+            .chain(configuration.chains.iter().map(|chain| &chain.contracts))
+            .enumerate()
+            .flat_map(|(chain_index, contracts)| contracts.iter().map(move |(identifier, _)| (chain_index, identifier)))
+            .map(|(chain_index, identifier)| {
+                feed_status.get(&(chain_index, identifier.clone())).cloned().unwrap_or_else(|| FeedStatus {
+                    chain_index,
+                    identifier: identifier.clone(),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Queries the decimals of every configured feed concurrently, keyed by `FeedKey`.
+    /// Fails with the first error encountered, which also makes it a quick check that
+    /// every feed is reachable and points to an aggregator. Overridden decimals are
+    /// returned as configured.
+    pub async fn all_decimals(&self) -> Result<HashMap<FeedKey, u8>, Error> {
+        let configuration = &self.configuration;
+        let call_timeout = Duration::from_secs(configuration.call_timeout_seconds);
+        let feeds = std::iter::once((self.active_provider(), &configuration.contracts))
+            .chain(configuration.chains.iter().map(|chain| (&chain.provider, &chain.contracts)))
+            .enumerate()
+            .flat_map(|(chain_index, (provider, contracts))| contracts.iter().map(move |contract| (chain_index, provider, contract)));
+
+        join_all(feeds.map(|(chain_index, provider, (identifier, address))| async move {
+            let feed = (chain_index, identifier.clone());
+            let decimals = match configuration.decimals_overrides.get(&feed) {
+                Some(decimals) => *decimals,
+                None => interface::ChainlinkContract::fetch_decimals(provider, *address, call_timeout).await?,
+            };
+            Ok((feed, decimals))
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+
+    /// Lists every configured feed in configuration order, with the decimals and description
+    /// its aggregator reports. Feeds are queried concurrently and a failure to query the
+    /// decimals of any of them fails the whole listing, as in [`Rustlink::all_decimals`].
+    pub async fn feeds(&self) -> Result<Vec<FeedInfo>, Error> {
+        let configuration = &self.configuration;
+        let call_timeout = Duration::from_secs(configuration.call_timeout_seconds);
+        let feeds = std::iter::once((self.active_provider(), &configuration.contracts))
+            .chain(configuration.chains.iter().map(|chain| (&chain.provider, &chain.contracts)))
+            .enumerate()
+            .flat_map(|(chain_index, (provider, contracts))| contracts.iter().map(move |contract| (chain_index, provider, contract)));
+
+        join_all(feeds.map(|(chain_index, provider, (identifier, address))| async move {
+            let decimals = match configuration.decimals_overrides.get(&(chain_index, identifier.clone())) {
+                Some(decimals) => *decimals,
+                None => interface::ChainlinkContract::fetch_decimals(provider, *address, call_timeout).await?,
+            };
+            let description = interface::ChainlinkContract::fetch_description(provider, *address, call_timeout)
+                .await
+                .ok();
+            Ok(FeedInfo {
+                identifier: identifier.clone(),
+                address: *address,
+                decimals,
+                description,
+            })
+        }))
+        .await
+        .into_iter()
+        .collect()
+    }
+
+    /// Fetches every configured feed once, concurrently, without starting the fetch loop or
+    /// involving the reflector. The results are in configuration order, one per feed.
+    pub async fn snapshot(&self) -> Vec<Result<Round, Error>> {
+        self.snapshot_until(future::pending().shared()).await
+    }
+
+    /// Like `snapshot`, returning once `deadline` elapsed at the latest. Feeds that have not
+    /// been fetched by then fail with `Error::Timeout`, the others keep their result.
+    pub async fn snapshot_with_deadline(&self, deadline: Duration) -> Vec<Result<Round, Error>> {
+        self.snapshot_until(workflow_rs::core::task::sleep(deadline).shared()).await
+    }
+
+    /// Fetches every configured feed once, giving up on those still pending when `deadline` completes.
+    async fn snapshot_until<F: Future<Output = ()>>(&self, deadline: Shared<F>) -> Vec<Result<Round, Error>> {
+        let configuration = &self.configuration;
+        let chains = std::iter::once((self.active_provider(), &configuration.contracts, configuration.chain_id))
+            .chain(configuration.chains.iter().map(|chain| (&chain.provider, &chain.contracts, chain.chain_id)));
+
+        let snapshots = join_all(chains.enumerate().map(|(chain_index, (provider, contracts, chain_id))| {
+            let deadline = deadline.clone();
+            async move {
+                let chain_id = match chain_id {
+                    Some(chain_id) => Ok(chain_id),
+                    None => {
+                        let chain_id = async {
+                            provider
+                                .get_chainid()
+                                .await
+                                .map(|chain_id| chain_id.as_u64())
+                                .map_err(|error| Error::Connection(error.to_string()))
+                        };
+                        before(chain_id, deadline.clone()).await
+                    }
+                };
+
+                join_all(contracts.iter().map(|(identifier, address)| {
+                    let fetch = async {
+                        match &chain_id {
+                            Ok(chain_id) => {
+                                let decimals = configuration.decimals_overrides.get(&(chain_index, identifier.clone())).copied();
+                                let call_timeout = Duration::from_secs(configuration.call_timeout_seconds);
+                                fetch_round_data_for_contract(provider, *chain_id, identifier, *address, configuration.call_from, decimals, call_timeout).await
+                            }
+                            Err(Error::Connection(error)) => Err(Error::Connection(error.clone())),
+                            Err(_) => Err(Error::Timeout),
+                        }
+                    };
+                    before(fetch, deadline.clone())
+                }))
+                .await
+            }
+        }))
+        .await;
+
+        snapshots.into_iter().flatten().collect()
+    }
+
+    /// Describes the effective configuration as JSON, e.g. to attach to a bug report.
+    /// Providers are left out entirely, so RPC urls and any API keys in them are never included.
+    pub fn describe(&self) -> serde_json::Value {
+        let configuration = &self.configuration;
+        let feeds = |contracts: &Vec<(String, Address)>| -> Vec<serde_json::Value> {
+            contracts
+                .iter()
+                .map(|(identifier, address)| json!({ "identifier": identifier, "address": to_checksum(address, None) }))
+                .collect()
+        };
+        let chains: Vec<_> = configuration
+            .chains
+            .iter()
+            .map(|chain| json!({ "chain_id": chain.chain_id, "contracts": feeds(&chain.contracts) }))
+            .collect();
+
+        json!({
+            "fetch_interval_seconds": configuration.fetch_interval_seconds,
+            "chain_id": configuration.chain_id,
+            "contracts": feeds(&configuration.contracts),
+            "chains": chains,
+            "stagger_start": configuration.stagger_start,
+            "tick_events": configuration.tick_events,
+            "batch_window_ms": configuration.batch_window.map(|window| window.as_millis() as u64),
+            "dedup": configuration.dedup,
+            "smoothing": configuration.smoothing.map(|smoothing| format!("{:?}", smoothing)),
+            "max_rounds": configuration.max_rounds,
+            "max_runtime_seconds": configuration.max_runtime.map(|runtime| runtime.as_secs_f64()),
+            "emit_on_start": configuration.emit_on_start,
+            "sanity_ranges": by_feed(&configuration.sanity_ranges),
+            "exact_answer": configuration.exact_answer,
+            "rpc_aggregation": configuration.rpc_aggregation.map(|aggregation| format!("{:?}", aggregation)),
+            "aggregation_providers": configuration.aggregation_providers.len(),
+            "fallback_providers": configuration.fallback_providers.len(),
+            "call_from": configuration.call_from.map(|call_from| to_checksum(&call_from, None)),
+            "strict_checksum": configuration.strict_checksum,
+            "error_budget": configuration.error_budget.map(|budget| format!("{:?}", budget)),
+            "phase_offset_ms": configuration.phase_offset.as_millis() as u64,
+            "conditional_fetch": configuration.conditional_fetch,
+            "quotes": by_feed(&configuration.quotes),
+            "quotes_from_description": configuration.quotes_from_description,
+            "freeze_threshold_seconds": configuration.freeze_threshold.map(|threshold| threshold.as_secs_f64()),
+            "decimals_overrides": by_feed(&configuration.decimals_overrides),
+            "align_to_interval": configuration.align_to_interval,
+            "call_timeout_seconds": configuration.call_timeout_seconds,
+            "max_retries": configuration.max_retries,
+            "retry_base_delay_ms": configuration.retry_base_delay_ms,
+            "retry_budget": configuration.retry_budget.map(|budget| format!("{:?}", budget)),
+            "decimals_check_ticks": configuration.decimals_check_ticks,
+        })
+    }
+
+    /// Checks that the RPC of the primary chain answers, without touching any feed contract.
+    /// Returns the round trip latency of an `eth_chainId` request, suitable for liveness probes.
+    pub async fn ping(&self) -> Result<Duration, Error> {
+        let started = workflow_rs::core::time::Instant::now();
+        self.active_provider()
+            .get_chainid()
+            .await
+            .map_err(|error| Error::Connection(error.to_string()))?;
+        Ok(started.elapsed())
+    }
+
+    /// Fetches the latest round of the feed with the given identifier right away, without
+    /// starting the fetch loop or involving the reflector. Fails with `Error::NotFound` if
+    /// no feed has this identifier.
+    pub async fn get_latest(&self, identifier: &str) -> Result<Round, Error> {
+        let configuration = &self.configuration;
+        let (chain_index, address, chain_id) = std::iter::once((&configuration.contracts, configuration.chain_id))
+            .chain(configuration.chains.iter().map(|chain| (&chain.contracts, chain.chain_id)))
+            .enumerate()
+            .find_map(|(chain_index, (contracts, chain_id))| {
+                contracts
+                    .iter()
+                    .find(|(configured, _)| configured == identifier)
+                    .map(|(_, address)| (chain_index, *address, chain_id))
+            })
+            .ok_or(Error::NotFound)?;
+
+        // Only the primary chain has fallback providers to fail over to
+        let feed = (chain_index, identifier.to_string());
+        if chain_index > 0 {
+            let provider = &configuration.chains[chain_index - 1].provider;
+            return self.fetch_latest(provider, &feed, address, chain_id).await;
+        }
+        let mut attempts = configuration.fallback_providers.len();
+        loop {
+            match self.fetch_latest(self.active_provider(), &feed, address, chain_id).await {
+                Err(error) if attempts > 0 && is_unreachable(&error) => {
+                    self.fail_over();
+                    attempts -= 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Fetches the latest round of a feed through `provider`, querying the chain id unless configured.
+    async fn fetch_latest(&self, provider: &M, feed: &FeedKey, address: Address, chain_id: Option<u64>) -> Result<Round, Error> {
+        let configuration = &self.configuration;
+        let chain_id = match chain_id {
+            Some(chain_id) => chain_id,
+            None => provider
+                .get_chainid()
+                .await
+                .map_err(|error| Error::Connection(error.to_string()))?
+                .as_u64(),
+        };
+        let decimals = configuration.decimals_overrides.get(feed).copied();
+        let call_timeout = Duration::from_secs(configuration.call_timeout_seconds);
+        fetch_round_data_for_contract(provider, chain_id, &feed.1, address, configuration.call_from, decimals, call_timeout).await
+    }
+
+    /// Like `get_latest`, failing with `Error::StaleData` when the round was updated longer
+    /// than `max_age` ago according to the configured clock, so that a stale price is never
+    /// acted upon. A bogus `updated_at` beyond `u64` fails with `Error::Deserialize`.
+    pub async fn get_latest_fresh(&self, identifier: &str, max_age: Duration) -> Result<Round, Error> {
+        let round = self.get_latest(identifier).await?;
+        let updated_at = u64::try_from(round.updated_at).map_err(|_| Error::Deserialize)?;
+        let age = Duration::from_millis(self.configuration.clock.now_millis())
+            .saturating_sub(Duration::from_secs(updated_at));
+        if age > max_age {
+            return Err(Error::StaleData {
+                identifier: round.identifier,
+                age,
+            });
+        }
+        Ok(round)
+    }
+
+    /// Returns a channel receiving only the rounds of the feed with the given identifier on the
+    /// primary chain, in addition to everything delivered to the reflector. Dropping the receiver
+    /// ends the subscription. Fails with `Error::NotFound` if no feed has this identifier.
+    pub fn subscribe(&self, identifier: &str) -> Result<Receiver<Round>, Error> {
+        self.subscribe_on(0, identifier)
+    }
+
+    /// Like `subscribe`, for the feed with the given identifier on the chain with the given
+    /// index, see `FeedKey`.
+    pub fn subscribe_on(&self, chain_index: usize, identifier: &str) -> Result<Receiver<Round>, Error> {
+        let configuration = &self.configuration;
+        let contracts = match chain_index {
+            0 => Some(&configuration.contracts),
+            _ => configuration.chains.get(chain_index - 1).map(|chain| &chain.contracts),
+        };
+        if !contracts.is_some_and(|contracts| contracts.iter().any(|(configured, _)| configured == identifier)) {
+            return Err(Error::NotFound);
+        }
+
+        let (sender, receiver) = async_std::channel::unbounded();
+        self.subscribers
+            .lock()
+            .unwrap()
+            .entry((chain_index, identifier.to_string()))
+            .or_default()
+            .push(sender);
+        Ok(receiver)
+    }
+
+    /// Returns the live subscribers of a feed, forgetting those whose receiver was dropped.
+    pub(crate) fn subscribers(&self, feed: &FeedKey) -> Vec<Sender<Round>> {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        match subscribers.get_mut(feed) {
+            Some(senders) => {
+                senders.retain(|sender| !sender.is_closed());
+                senders.clone()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Records the outcome of a fetch in the status of the feed.
+    pub(crate) fn record_fetch(&self, feed: &FeedKey, success: bool) {
+        let now = self.configuration.clock.now_millis();
+        let mut feed_status = self.feed_status.lock().unwrap();
+        let status = feed_status
+            .entry(feed.clone())
+            .or_insert_with(|| FeedStatus {
+                chain_index: feed.0,
+                identifier: feed.1.clone(),
+                ..Default::default()
+            });
+
+        if success {
+            status.last_success = Some(now);
+            status.consecutive_failures = 0;
+        } else {
+            status.last_error = Some(now);
+            status.consecutive_failures += 1;
+        }
     }
 
     /// Stops the Rustlink instance.
     /// This method will stop fetching the latest price data from the Chainlink decentralized data feed.
+    /// Returns once fetching has stopped. Stopping an instance that is not running, because it was
+    /// never started, stopped already or stopped on its own, does nothing.
+    ///
+    /// Once the loop ends, the channel of a `Reflector::Sender` or `Reflector::BatchSender` and
+    /// every subscription are closed, so receivers see the end of the stream. Such an instance
+    /// has nowhere to deliver rounds and refuses to `start` again, build a new one with a fresh
+    /// channel instead.
+    /// `Reflector::Events` receives `FetchEvent::Closed` and stays usable across restarts.
     pub async fn stop(&self) -> Result<(), RecvError> {
+        if !self.running.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
         self.termination_send.send(()).await.unwrap();
         self.shutdown_recv.recv().await
     }
 }
 
+/// Converts a map keyed by `FeedKey` into JSON objects keyed by chain index, then identifier.
+fn by_feed<V: serde::Serialize>(map: &HashMap<FeedKey, V>) -> serde_json::Value {
+    let mut chains = serde_json::Map::new();
+    for ((chain_index, identifier), value) in map {
+        let chain = chains.entry(chain_index.to_string()).or_insert_with(|| json!({}));
+        chain[identifier] = json!(value);
+    }
+    chains.into()
+}
+
+/// Awaits `fetch`, failing with `Error::Timeout` if `deadline` completes first.
+pub(crate) async fn before<T>(fetch: impl Future<Output = Result<T, Error>>, deadline: impl Future<Output = ()>) -> Result<T, Error> {
+    futures::pin_mut!(fetch, deadline);
+    match future::select(fetch, deadline).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(Error::Timeout),
+    }
+}
+
+/// Parses the user supplied contract addresses. With `strict_checksum`, mixed case
+/// addresses must be valid EIP-55 checksums.
+pub(crate) fn parse_contracts(
+    contracts: Vec<(String, String)>,
+    strict_checksum: bool,
+) -> Result<Vec<(String, Address)>, Error> {
+    contracts
+        .into_iter()
+        .map(|(identifier, address)| {
+            let parsed = Address::from_str(&address).map_err(|error| {
+                Error::InvalidAddress(format!("{} ({}): {}", address, identifier, error))
+            })?;
+
+            let digits = address.trim_start_matches("0x");
+            let mixed_case = digits != digits.to_lowercase() && digits != digits.to_uppercase();
+            let checksum = to_checksum(&parsed, None);
+            if strict_checksum && mixed_case && checksum.trim_start_matches("0x") != digits {
+                return Err(Error::InvalidAddress(format!(
+                    "{} ({}): invalid checksum, expected {}",
+                    address, identifier, checksum
+                )));
+            }
+            Ok((identifier, parsed))
+        })
+        .collect()
+}
+
+/// Merges the rounds of several streams into one, such as the receivers of one Rustlink instance
+/// per chain. Streams are polled in turn so a busy one does not starve the others, and the merged
+/// stream ends once all of them have.
+pub fn merge_streams<S: Stream<Item = Round> + Unpin>(streams: Vec<S>) -> impl Stream<Item = Round> {
+    stream::select_all(streams)
+}
+
+/// Computes the price of an index or derived asset as the sum of its component answers times
+/// their weight. Each answer is derived from `raw_answer` with a single rounding rather than
+/// taken from `answer`. `None` when there are no components, when a component is stale, i.e.
+/// answered in an earlier round than its own, or when the result is not finite.
+pub fn weighted_index(components: &[(Round, f64)]) -> Option<f64> {
+    if components.is_empty() {
+        return None;
+    }
+    let mut index = 0.0;
+    for (round, weight) in components {
+        if round.answered_in_round < round.round_id {
+            return None;
+        }
+        index += round.answer_string().parse::<f64>().ok()? * weight;
+    }
+    index.is_finite().then_some(index)
+}
+
+/// Parses a compact `chain:identifier:address` feed spec, such as
+/// `1:ETH:0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419`, as written on command lines and
+/// in environment variables.
+pub fn parse_feed_spec(spec: &str) -> Result<(u32, String, Address), Error> {
+    let fields: Vec<&str> = spec.split(':').collect();
+    let [chain_id, identifier, address] = fields[..] else {
+        return Err(Error::InvalidFeedSpec(format!(
+            "{}: expected chain:identifier:address, found {} fields",
+            spec,
+            fields.len()
+        )));
+    };
+    let chain_id = chain_id
+        .parse()
+        .map_err(|error| Error::InvalidFeedSpec(format!("{}: chain id {}: {}", spec, chain_id, error)))?;
+    if identifier.is_empty() {
+        return Err(Error::InvalidFeedSpec(format!("{}: empty identifier", spec)));
+    }
+    let address = Address::from_str(address)
+        .map_err(|error| Error::InvalidAddress(format!("{} ({}): {}", address, identifier, error)))?;
+    Ok((chain_id, identifier.to_string(), address))
+}
+
 /// RustlinkJS is a JavaScript wrapper for Rustlink.
 /// It allows you to create a Rustlink instance in JavaScript and start fetching data when you use WASM.
 /// You should use this one when you want to use Rustlink in a web environment.
@@ -155,6 +983,11 @@ pub struct RustlinkJS {
     rustlink: Rustlink,
     callback: Function,
     receiver: Receiver<Round>,
+    batch_interval: Option<Duration>,
+    on_error: Option<Function>,
+    /// Whether a task is delivering rounds to the callback, which outlives the fetch loop
+    /// until the rounds left in the channel are delivered
+    consuming: Arc<AtomicBool>,
 }
 
 cfg_if! {
@@ -189,8 +1022,15 @@ impl RustlinkJS {
     /// Expected parameters:
     /// - `rpc_url`: The RPC url of your chosen EVM network where Chainlink offers decentralised data feeds.
     /// - `fetch_interval_seconds`: How often to update data points (to prevent RPC rate limitation)
-    /// - `contracts`: A list of tuples containing a ticker name and its corresponding contract address on the EVM chain
-    /// - `callback`: A JavaScript function (async or sync) that will be called every time a new data point is fetched
+    /// - `contracts`: A list of tuples containing a ticker name and its corresponding contract address on the EVM chain.
+    ///   Feeds are fetched one at a time in this order, so rounds are always emitted in configuration order
+    /// - `callback`: A JavaScript function (async or sync) that will be called every time a new data point is fetched.
+    ///   The promise of an async callback is awaited before the callback is called with the next round
+    ///
+    /// A callback that throws, or returns a promise that rejects, does not stop the instance. The error is
+    /// passed to the handler registered with `set_on_error`, or logged to the console without one.
+    ///
+    /// Throws an `Error` describing the problem when the RPC url or a contract is invalid.
     /// ```javascript
     /// import init, { RustlinkJS } from '../web/rustlink.js';
     ///
@@ -226,43 +1066,103 @@ impl RustlinkJS {
         fetch_interval_seconds: u64,
         contracts: Contracts,
         callback: Function,
-    ) -> Self {
-
-        
-        // Cast `JsValue` to `Function`
-
-        let contracts: Vec<(String, String)> = from_value(contracts.into()).unwrap();
+    ) -> Result<RustlinkJS, JsValue> {
+        let contracts: Vec<(String, String)> = from_value(contracts.into())
+            .map_err(|e| js_sys::Error::new(&format!("Invalid contracts: {}", e)))?;
 
         let (sender, receiver) = async_std::channel::unbounded();
         let reflector = Reflector::Sender(sender);
-        let rustlink = Rustlink::try_new(rpc_url, fetch_interval_seconds, reflector, contracts)
-            .map_err(|e| JsValue::from_str(&format!("{}", e)))
-            .unwrap();
+        let rustlink = Rustlink::try_new(vec![rpc_url.to_string()], fetch_interval_seconds, reflector, contracts)
+            .map_err(|e| js_sys::Error::new(&e.to_string()))?;
 
-        RustlinkJS {
+        Ok(RustlinkJS {
             rustlink,
             callback,
             receiver,
-        }
+            batch_interval: None,
+            on_error: None,
+            consuming: Default::default(),
+        })
+    }
+
+    /// Registers a JavaScript function called with the error whenever the callback throws or
+    /// its promise rejects, instead of logging it to the console.
+    #[wasm_bindgen]
+    pub fn set_on_error(&mut self, on_error: Function) {
+        self.on_error = Some(on_error);
+    }
+
+    /// Coalesces rounds and calls the callback with an array of the rounds received since the
+    /// previous call every `batch_interval_ms` milliseconds, instead of once per round. Intervals
+    /// without rounds are skipped.
+    /// Passing `0` restores the default of one call per round. Takes effect on the next `start`.
+    #[wasm_bindgen]
+    pub fn set_batch_interval_ms(&mut self, batch_interval_ms: u64) {
+        self.batch_interval = (batch_interval_ms > 0).then(|| Duration::from_millis(batch_interval_ms));
     }
 
     /// Starts the RustlinkJS instance.
     /// This method will start fetching the latest price data from the Chainlink decentralized data feed.
+    /// Throws if the instance is already running or still delivering rounds to the callback,
+    /// or was stopped and cannot be restarted.
     #[wasm_bindgen]
-    pub fn start(&self) {
-        self.rustlink.start();
+    pub fn start(&self) -> Result<(), JsValue> {
+        if self.rustlink.is_running() || self.consuming.load(Ordering::SeqCst) {
+            return Err(js_sys::Error::new("Rustlink is already running").into());
+        }
+        self.rustlink
+            .start()
+            .map_err(|_| js_sys::Error::new("Rustlink was stopped, create a new instance"))?;
+        self.consuming.store(true, Ordering::SeqCst);
         let receiver = self.receiver.clone();
         let callback = self.callback.clone();
+        let on_error = self.on_error.clone();
+        let consuming = self.consuming.clone();
+        if let Some(batch_interval) = self.batch_interval {
+            spawn_local(async move {
+                let ticks = workflow_rs::core::task::interval(batch_interval).fuse();
+                futures::pin_mut!(ticks);
+                let mut rounds = Vec::new();
+                loop {
+                    futures::select! {
+                        round = receiver.recv().fuse() => match round {
+                            Ok(round) => rounds.push(round),
+                            Err(_) => break,
+                        },
+                        _ = ticks.next() => {
+                            if !rounds.is_empty() {
+                                let arg_js = to_value(&std::mem::take(&mut rounds)).unwrap();
+                                call_callback(&callback, &on_error, &arg_js).await;
+                            }
+                        }
+                    }
+                }
+
+                // Deliver the rounds received since the last call before the channel closed
+                if !rounds.is_empty() {
+                    call_callback(&callback, &on_error, &to_value(&rounds).unwrap()).await;
+                }
+                consuming.store(false, Ordering::SeqCst);
+            });
+            return Ok(());
+        }
         spawn_local(async move {
             while let Ok(round) = receiver.recv().await {
                 // Prepare arguments to pass to JS function
-                let this = JsValue::NULL; // 'this' context for function, null in this case
                 let arg_js = to_value(&round).unwrap();
 
                 // Call the function
-                let _ = callback.call1(&this, &arg_js);
+                call_callback(&callback, &on_error, &arg_js).await;
             }
+            consuming.store(false, Ordering::SeqCst);
         });
+        Ok(())
+    }
+
+    /// Whether the instance is fetching, from `start` until it is stopped or stops on its own.
+    #[wasm_bindgen(getter)]
+    pub fn running(&self) -> bool {
+        self.rustlink.is_running()
     }
 
     /// Stops the RustlinkJS instance.
@@ -275,3 +1175,24 @@ impl RustlinkJS {
             .map_err(|e| JsValue::from_str(&format!("Shutdown error: {}", e)))
     }
 }
+
+/// Calls the JavaScript callback with `arg`, waiting for the promise an async callback returns
+/// so that rounds are handled one at a time and in order. A throw or a rejection is passed
+/// to `on_error`, or logged to the console without one.
+async fn call_callback(callback: &Function, on_error: &Option<Function>, arg: &JsValue) {
+    let result = match callback.call1(&JsValue::NULL, arg) {
+        Ok(value) => match value.dyn_into::<Promise>() {
+            Ok(promise) => JsFuture::from(promise).await,
+            Err(value) => Ok(value),
+        },
+        Err(error) => Err(error),
+    };
+    if let Err(error) = result {
+        match on_error {
+            Some(on_error) => {
+                let _ = on_error.call1(&JsValue::NULL, &error);
+            }
+            None => web_sys::console::error_2(&"Rustlink callback failed:".into(), &error),
+        }
+    }
+}
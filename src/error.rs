@@ -6,4 +6,36 @@ pub enum Error {
     NotFound,
     #[error("Could not deserialize binary data")]
     Deserialize,
+    #[error("Invalid RPC URL {0}")]
+    InvalidRpcUrl(String),
+    #[error("Invalid contract address {0}")]
+    InvalidAddress(String),
+    #[error("Invalid feed spec {0}")]
+    InvalidFeedSpec(String),
+    #[error("Could not connect to the provider: {0}")]
+    Connection(String),
+    #[error("Could not build the HTTP client: {0}")]
+    HttpClient(String),
+    #[error("Contract call failed: {0}")]
+    Call(String),
+    #[error("Contract of {identifier} reverted, check that it is a Chainlink aggregator")]
+    ContractReverted { identifier: String },
+    #[error("Round {0} not found, it may predate the first round of the feed")]
+    RoundNotFound(u128),
+    #[error("Feed requires an off-chain lookup through {0:?}")]
+    OffchainLookup(Vec<String>),
+    #[error("Latest round of {identifier} is {age:?} old")]
+    StaleData { identifier: String, age: std::time::Duration },
+    #[error("Timed out")]
+    Timeout,
+    #[error("Rate limited by the provider: {0}")]
+    RateLimited(String),
+    #[error("Fetch panicked: {0}")]
+    Panicked(String),
+    #[error("The channel of the reflector is closed, build a new instance with a fresh channel")]
+    ReflectorClosed,
+    #[error("Combining the answers of the providers overflowed")]
+    AggregationOverflow,
+    #[error("Invalid contract ABI: {0}")]
+    Abi(#[from] ethers::abi::AbiError),
 }
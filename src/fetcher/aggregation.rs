@@ -0,0 +1,99 @@
+use ethers::types::I256;
+
+use crate::core::{Round, RpcAggregation};
+use crate::error::Error;
+use crate::interface::scale;
+
+/// Combines the results of fetching a feed from every provider into one round.
+/// Fails when none of the providers answered, with the error of the last one, or when
+/// combining their answers overflows.
+pub fn aggregate(results: Vec<Result<Round, Error>>, aggregation: RpcAggregation, identifier: &str) -> Result<Round, Error> {
+    let mut rounds = Vec::with_capacity(results.len());
+    let mut last_error = None;
+    for result in results {
+        match result {
+            Ok(round) => rounds.push(round),
+            Err(error) => {
+                log::warn!("Leaving an RPC out of the answer of {}: {}", identifier, error);
+                last_error = Some(error);
+            }
+        }
+    }
+
+    match (combine(rounds, aggregation)?, last_error) {
+        (Some(round), _) => Ok(round),
+        (None, Some(error)) => Err(error),
+        (None, None) => Err(Error::NotFound),
+    }
+}
+
+/// Combines the answers of the rounds into the oldest of them.
+fn combine(mut rounds: Vec<Round>, aggregation: RpcAggregation) -> Result<Option<Round>, Error> {
+    let mut answers: Vec<I256> = rounds.iter().map(|round| round.raw_answer).collect();
+    answers.sort_unstable();
+    let count = I256::from(answers.len());
+
+    let raw_answer = match aggregation {
+        _ if answers.is_empty() => return Ok(None),
+        RpcAggregation::Mean => {
+            let sum = answers.iter().try_fold(I256::zero(), |sum, answer| sum.checked_add(*answer));
+            sum.ok_or(Error::AggregationOverflow)? / count
+        }
+        RpcAggregation::Median if answers.len() % 2 == 1 => answers[answers.len() / 2],
+        RpcAggregation::Median => {
+            let sum = answers[answers.len() / 2 - 1].checked_add(answers[answers.len() / 2]);
+            sum.ok_or(Error::AggregationOverflow)? / I256::from(2)
+        }
+    };
+
+    rounds.sort_by_key(|round| round.updated_at);
+    let mut round = rounds.swap_remove(0);
+    round.answer = scale(raw_answer, round.decimals)?;
+    round.raw_answer = raw_answer;
+    Ok(Some(round))
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{I256, U256};
+
+    use super::combine;
+    use crate::core::{Round, RpcAggregation};
+    use crate::error::Error;
+
+    fn round(raw_answer: i64, updated_at: u64) -> Round {
+        Round {
+            identifier: "ETH".to_string(),
+            raw_answer: I256::from(raw_answer),
+            decimals: 2,
+            updated_at: U256::from(updated_at),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn combines_answers() {
+        let rounds = vec![round(300, 12), round(100, 10), round(110, 11)];
+
+        let median = combine(rounds.clone(), RpcAggregation::Median).unwrap().unwrap();
+        assert_eq!((median.raw_answer, median.answer), (I256::from(110), 1.1));
+        assert_eq!(median.updated_at, U256::from(10));
+
+        let mean = combine(rounds, RpcAggregation::Mean).unwrap().unwrap();
+        assert_eq!(mean.raw_answer, I256::from(170));
+
+        let median = combine(vec![round(100, 10), round(110, 11)], RpcAggregation::Median).unwrap().unwrap();
+        assert_eq!(median.raw_answer, I256::from(105));
+        assert!(combine(Vec::new(), RpcAggregation::Mean).unwrap().is_none());
+    }
+
+    #[test]
+    fn overflowing_answers_fail() {
+        let mut huge = round(0, 10);
+        huge.raw_answer = I256::MAX;
+        let rounds = vec![huge.clone(), huge];
+
+        assert!(matches!(combine(rounds.clone(), RpcAggregation::Mean), Err(Error::AggregationOverflow)));
+        assert!(matches!(combine(rounds, RpcAggregation::Median), Err(Error::AggregationOverflow)));
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use workflow_rs::core::time::Instant;
+
+use crate::core::Round;
+
+/// Coalesces the rounds delivered to a `Reflector::BatchSender`.
+pub struct Batch {
+    window: Option<Duration>,
+    rounds: Vec<Round>,
+    started: Option<Instant>,
+}
+
+impl Batch {
+    /// Creates an empty batch. Without a window, a batch spans one pass over the contracts.
+    pub fn new(window: Option<Duration>) -> Self {
+        Batch {
+            window,
+            rounds: Vec::new(),
+            started: None,
+        }
+    }
+
+    /// Adds a round to the batch, starting the window if it is the first one.
+    pub fn push(&mut self, round: Round) {
+        self.started.get_or_insert_with(Instant::now);
+        self.rounds.push(round);
+    }
+
+    /// Whether the batch should be emitted now.
+    pub fn is_due(&self, end_of_pass: bool) -> bool {
+        match (self.started, self.window) {
+            (None, _) => false,
+            (Some(_), None) => end_of_pass,
+            (Some(started), Some(window)) => started.elapsed() >= window,
+        }
+    }
+
+    /// How long until the window of the batch is over, `None` when it is empty or spans a pass.
+    pub fn remaining(&self) -> Option<Duration> {
+        match (self.started, self.window) {
+            (Some(started), Some(window)) => Some(window.saturating_sub(started.elapsed())),
+            _ => None,
+        }
+    }
+
+    /// Empties the batch, returning the rounds it held.
+    pub fn take(&mut self) -> Vec<Round> {
+        self.started = None;
+        std::mem::take(&mut self.rounds)
+    }
+}
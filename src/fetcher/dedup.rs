@@ -0,0 +1,81 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::core::{FeedKey, Round};
+
+/// Number of rounds remembered for deduplication, bounding memory use.
+const DEDUP_CAPACITY: usize = 1024;
+
+/// Suppresses rounds whose id was already emitted for the same feed.
+/// Only the most recent `DEDUP_CAPACITY` rounds are remembered.
+pub struct Dedup {
+    seen: HashSet<(FeedKey, u128)>,
+    order: VecDeque<(FeedKey, u128)>,
+}
+
+impl Dedup {
+    pub fn new() -> Self {
+        Dedup {
+            seen: HashSet::with_capacity(DEDUP_CAPACITY),
+            order: VecDeque::with_capacity(DEDUP_CAPACITY),
+        }
+    }
+
+    /// Records the round of `feed`, returning whether it had not been seen before.
+    pub fn insert(&mut self, feed: &FeedKey, round: &Round) -> bool {
+        let key = (feed.clone(), round.round_id);
+        if !self.seen.insert(key.clone()) {
+            return false;
+        }
+
+        self.order.push_back(key);
+        if self.order.len() > DEDUP_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dedup, DEDUP_CAPACITY};
+    use crate::core::{FeedKey, Round};
+
+    fn feed(chain_index: usize) -> FeedKey {
+        (chain_index, "ETH".to_string())
+    }
+
+    fn round(round_id: u128) -> Round {
+        Round {
+            identifier: "ETH".to_string(),
+            chain_id: 56,
+            round_id,
+            answered_in_round: round_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn suppresses_seen_round_ids() {
+        let mut dedup = Dedup::new();
+        assert!(dedup.insert(&feed(0), &round(1)));
+        assert!(!dedup.insert(&feed(0), &round(1)));
+        assert!(dedup.insert(&feed(0), &round(2)));
+
+        // The oldest round is forgotten once the capacity is exceeded
+        for round_id in 3..=DEDUP_CAPACITY as u128 + 1 {
+            assert!(dedup.insert(&feed(0), &round(round_id)));
+        }
+        assert!(dedup.insert(&feed(0), &round(1)));
+    }
+
+    #[test]
+    fn chains_are_deduplicated_separately() {
+        let mut dedup = Dedup::new();
+        assert!(dedup.insert(&feed(0), &round(1)));
+        // Another provider of the same chain id added as a separate chain
+        assert!(dedup.insert(&feed(1), &round(1)));
+        assert!(!dedup.insert(&feed(1), &round(1)));
+    }
+}
@@ -1,66 +1,568 @@
+use std::collections::{hash_map::Entry, HashMap};
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use async_std::stream::StreamExt;
-use ethers::abi::AbiError;
-use ethers::types::Address;
+use ethers::providers::Middleware;
+use ethers::types::{Address, U256};
+use futures::future::{self, join_all, Either};
 use futures::{select, FutureExt};
+use rand::Rng;
 
-use super::interface::{ChainlinkContract, Round};
-use crate::core::Reflector::Sender;
-use crate::core::{Configuration, Rustlink};
+use super::interface::{quote_of, ChainlinkContract, Round};
+use crate::core::Reflector::{self, BatchSender, Callback, Custom, Events, Sender, Stdout};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::Reflector::Broadcast;
+use crate::core::{Configuration, FeedKey, FetchEvent, Rustlink};
+use crate::error::Error;
+use aggregation::aggregate;
+use batch::Batch;
+use dedup::Dedup;
+use previous::Previous;
+use retries::Retries;
+use smoothing::Smoothing;
+use velocity::Velocity;
 
-/// Retrieves the price of an underlying asset from a particular contract
-async fn fetch_round_data_for_contract(
-    rustlink_configuration: &Configuration,
+mod aggregation;
+mod batch;
+mod dedup;
+mod previous;
+mod retries;
+pub(crate) mod replay;
+mod smoothing;
+mod velocity;
+
+/// Retrieves the price of an underlying asset from a particular contract,
+/// using `decimals` rather than querying them when given.
+pub(crate) async fn fetch_round_data_for_contract<M: Middleware + Clone>(
+    provider: &M,
+    chain_id: u64,
     identifier: &str,
     address: Address,
-) -> Result<Round, AbiError> {
-    let contract =
-        ChainlinkContract::new(&rustlink_configuration.provider, identifier, address).await?;
-    contract.latest_round_data().await
+    call_from: Option<Address>,
+    decimals: Option<u8>,
+    call_timeout: Duration,
+) -> Result<Round, Error> {
+    let contract = connect(provider, chain_id, identifier, address, call_from, decimals, call_timeout).await?;
+    fetch_round(&contract).await
+}
+
+/// Creates the contract of a feed, using `decimals` rather than querying them when given.
+async fn connect<'a, M: Middleware + Clone>(
+    provider: &'a M,
+    chain_id: u64,
+    identifier: &'a str,
+    address: Address,
+    call_from: Option<Address>,
+    decimals: Option<u8>,
+    call_timeout: Duration,
+) -> Result<ChainlinkContract<'a, M>, Error> {
+    let mut contract =
+        ChainlinkContract::with_call_timeout(provider, identifier, address, chain_id, decimals, call_timeout).await?;
+    contract.call_from = call_from;
+    Ok(contract)
+}
+
+/// Retrieves the latest round of a contract
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(name = "fetch", skip_all, fields(chain_id = contract.chain_id, identifier = contract.identifier))
+)]
+async fn fetch_round<M: Middleware + Clone>(contract: &ChainlinkContract<'_, M>) -> Result<Round, Error> {
+    #[cfg(feature = "tracing")]
+    let started = workflow_rs::core::time::Instant::now();
+
+    let result = contract.latest_round_data().await;
+
+    #[cfg(feature = "tracing")]
+    {
+        let latency_ms = started.elapsed().as_millis() as u64;
+        match &result {
+            Ok(round) => tracing::info!(round_id = round.round_id, latency_ms, "fetched round"),
+            Err(error) => tracing::error!(%error, latency_ms, "fetch failed"),
+        }
+    }
+    result
+}
+
+/// Whether a fetch failed because the provider could not be reached or did not answer in
+/// time, in which case another provider may do better.
+pub(crate) fn is_unreachable(error: &Error) -> bool {
+    matches!(error, Error::Connection(_) | Error::Timeout)
+}
+
+/// Returns the chain id of a provider, querying it only the first time
+async fn resolve_chain_id<M: Middleware>(
+    provider: &M,
+    chain_id: &mut Option<u64>,
+) -> Option<u64> {
+    if chain_id.is_none() {
+        match provider.get_chainid().await {
+            Ok(resolved) => *chain_id = Some(resolved.as_u64()),
+            Err(error) => log::error!("Failed resolving chain id: {}", error),
+        }
+    }
+    *chain_id
+}
+
+/// How long to wait before the first tick: the configured phase offset, after aligning it to the
+/// interval if asked to, plus a random fraction of the interval so that many instances started
+/// together do not hit the RPC in the same second.
+fn start_delay<M: Middleware>(configuration: &Configuration<M>) -> Duration {
+    let interval_ms = (configuration.fetch_interval_seconds * 1000).max(1);
+    let mut delay = configuration.phase_offset;
+    if configuration.align_to_interval {
+        delay += Duration::from_millis((interval_ms - configuration.clock.now_millis() % interval_ms) % interval_ms);
+    }
+    if configuration.stagger_start {
+        delay += Duration::from_millis(rand::thread_rng().gen_range(0..interval_ms));
+    }
+    delay
 }
 
 // The function signature looks good, but ensure all types (Rustlink, Round, etc.) are properly defined.
-pub async fn fetch_rounds(rustlink: Rustlink) {
-    let contracts = &rustlink.configuration.contracts;
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(interval_seconds = rustlink.configuration.fetch_interval_seconds)))]
+pub async fn fetch_rounds<M: Middleware + Clone + 'static>(rustlink: Rustlink<M>) {
+    let configuration = &rustlink.configuration;
+    // The primary chain followed by every additional chain, multiplexed into one loop
+    let chains: Vec<_> = std::iter::once((&configuration.provider, &configuration.contracts))
+        .chain(configuration.chains.iter().map(|chain| (&chain.provider, &chain.contracts)))
+        .collect();
+    // Configured chain ids are used as is, the others are queried once
+    let mut chain_ids: Vec<Option<u64>> = std::iter::once(configuration.chain_id)
+        .chain(configuration.chains.iter().map(|chain| chain.chain_id))
+        .collect();
     let mut shutdown_future = rustlink.termination_recv.recv().fuse();
-    let worker_future = workflow_rs::core::task::interval(Duration::from_secs(
-        rustlink.configuration.fetch_interval_seconds,
-    ));
+    let interval = Duration::from_secs(configuration.fetch_interval_seconds);
+    let call_timeout = Duration::from_secs(configuration.call_timeout_seconds);
+    let retries = Retries::new(
+        configuration.max_retries,
+        Duration::from_millis(configuration.retry_base_delay_ms),
+        configuration.retry_budget,
+        configuration.clock.clone(),
+    );
+
+    let delay = start_delay(configuration);
+    if !delay.is_zero() {
+        select! {
+            _ = shutdown_future => {
+                close(&rustlink).await;
+                rustlink.shutdown_send.send(()).await.unwrap();
+                return;
+            },
+            _ = workflow_rs::core::task::sleep(delay).fuse() => {}
+        }
+    }
+
+    let worker_future = workflow_rs::core::task::interval(interval);
     futures::pin_mut!(worker_future);
+    let mut batch = Batch::new(configuration.batch_window);
+    let mut dedup = configuration.dedup.then(Dedup::new);
+    let mut smoothing = configuration.smoothing.map(Smoothing::new);
+    let mut velocity = Velocity::new();
+    let mut previous = Previous::new();
+    // The `latestTimestamp` of every feed at its last full fetch, for conditional fetches
+    let mut timestamps: HashMap<(usize, Address), U256> = HashMap::new();
+    // The contract of every feed per provider, keyed by chain and provider index
+    let mut connected: HashMap<(usize, usize, Address), ChainlinkContract<'_, M>> = HashMap::new();
+    // The `updated_at` of the latest round of every feed reported as frozen
+    let mut frozen: HashMap<(usize, Address), u64> = HashMap::new();
 
-    // This loop runs indefinitely, fetching price data.
-    loop {
-        for contract_configuration in contracts {
-            select! {
-                    _ = shutdown_future => {
-                        rustlink.shutdown_send.send(()).await.unwrap();
-                        return;
-                    },
-                    _ = worker_future.next().fuse() => {
+    // Stop on our own once the configured limits are reached
+    let mut emitted_rounds: u64 = 0;
+    let deadline = match configuration.max_runtime {
+        Some(max_runtime) => Either::Left(workflow_rs::core::task::sleep(max_runtime)),
+        None => Either::Right(future::pending()),
+    }
+    .fuse();
+    futures::pin_mut!(deadline);
+
+    // The first pass skips the interval when a warmup was requested
+    let mut warmup = configuration.emit_on_start;
+    // Consecutive passes in which every fetch failed, for the error budget
+    let mut failed_passes: u32 = 0;
+    // Passes completed so far, for the periodic decimals check
+    let mut passes: u64 = 0;
+
+    // This loop runs until stopped, fetching price data.
+    'fetching: loop {
+        let mut pass_succeeded = false;
+        let check_decimals = configuration
+            .decimals_check_ticks
+            .is_some_and(|ticks| passes > 0 && passes.is_multiple_of(u64::from(ticks.max(1))));
+        for (chain_index, (provider, contracts)) in chains.iter().enumerate() {
+            for contract_configuration in contracts.iter() {
+                let tick = match warmup {
+                    true => Either::Left(future::ready(())),
+                    false => Either::Right(worker_future.next().map(drop)),
+                };
+                let mut tick = tick.fuse();
+                loop {
+                    // Emit a batch once its window is over, rather than with the next fetch
+                    let flush = match batch.remaining() {
+                        Some(remaining) => Either::Left(workflow_rs::core::task::sleep(remaining)),
+                        None => Either::Right(future::pending()),
+                    };
+                    select! {
+                        _ = shutdown_future => break 'fetching,
+                        _ = deadline => break 'fetching,
+                        _ = flush.fuse() => reflect_batch(&rustlink.reflector, batch.take()).await,
+                        _ = tick => break,
+                    }
+                }
 
                 let identifier = &contract_configuration.0; // This variable wasn't used in your original code.
                 let address = &contract_configuration.1;
+                let feed = (chain_index, identifier.clone());
+                let provider = match chain_index {
+                    0 => rustlink.active_provider(),
+                    _ => *provider,
+                };
 
-                // Fetch price data and attempt to send it via the channel.
-                match fetch_round_data_for_contract(&rustlink.configuration, identifier, *address).await
-                {
-                    Ok(price_data) => {
-                        match rustlink.reflector {
-                            Sender(ref sender) => {
-                                // Attempt to send the PriceData through the channel.
-                                if let Err(error) = sender.send(price_data).await {
-                                    log::error!("Failed sending data: {}", error);
+                let Some(chain_id) = resolve_chain_id(provider, &mut chain_ids[chain_index]).await else {
+                    rustlink.record_fetch(&feed, false);
+                    continue;
+                };
+
+                // Skip the whole round when the feed was not updated since it was last fetched
+                let timestamp = match configuration.conditional_fetch {
+                    true => ChainlinkContract::fetch_latest_timestamp(provider, *address, configuration.call_from, call_timeout).await.ok(),
+                    false => None,
+                };
+                if timestamp.is_some() && timestamps.get(&(chain_index, *address)) == timestamp.as_ref() {
+                    log::debug!("{} was not updated since the last fetch", identifier);
+                    rustlink.record_fetch(&feed, true);
+                    pass_succeeded = true;
+                    continue;
+                }
+
+                // Only the primary chain can fail over, and only when not aggregating its providers
+                let mut failovers = match (chain_index, configuration.rpc_aggregation) {
+                    (0, None) => configuration.fallback_providers.len(),
+                    _ => 0,
+                };
+                let result = loop {
+                    // Fetch price data and attempt to send it via the channel.
+                    // Only the primary chain can have aggregation providers. The providers are
+                    // keyed by their index, which is that of the active provider when failing over.
+                    let providers: Vec<(usize, &M)> = match configuration.rpc_aggregation {
+                        Some(_) if chain_index == 0 => std::iter::once(provider)
+                            .chain(configuration.aggregation_providers.iter())
+                            .enumerate()
+                            .collect(),
+                        None if chain_index == 0 => vec![(rustlink.active_provider.load(Ordering::SeqCst), rustlink.active_provider())],
+                        _ => vec![(0, provider)],
+                    };
+
+                    // Contracts are created once per feed and provider, then reused by every fetch
+                    let decimals = configuration.decimals_overrides.get(&feed).copied();
+                    // Upgraded aggregators may report other decimals than those the contracts were created with
+                    if check_decimals && decimals.is_none() {
+                        let mut changed = None;
+                        for (provider_index, provider) in providers.iter() {
+                            let key = (chain_index, *provider_index, *address);
+                            let Some(old) = connected.get(&key).map(|contract| contract.decimals) else {
+                                continue;
+                            };
+                            match ChainlinkContract::fetch_decimals(*provider, *address, call_timeout).await {
+                                Ok(new) if new != old => {
+                                    connected.remove(&key);
+                                    changed = Some((old, new));
+                                }
+                                Ok(_) => {}
+                                Err(error) => log::warn!("Checking the decimals of {} failed: {}", identifier, error),
+                            }
+                        }
+                        if let Some((old, new)) = changed {
+                            log::warn!("Decimals of {} changed from {} to {}", identifier, old, new);
+                            let event = FetchEvent::DecimalsChanged { identifier: identifier.clone(), old, new };
+                            reflect_event(&rustlink.reflector, event).await;
+                        }
+                    }
+                    let mut results = Vec::with_capacity(providers.len());
+                    for (provider_index, provider) in providers.iter() {
+                        if let Entry::Vacant(entry) = connected.entry((chain_index, *provider_index, *address)) {
+                            let contract = retries.run(identifier, || {
+                                connect(*provider, chain_id, identifier, *address, configuration.call_from, decimals, call_timeout)
+                            });
+                            match catch_panic(contract).await {
+                                Ok(contract) => {
+                                    entry.insert(contract);
                                 }
+                                Err(error) => results.push(Err(error)),
                             }
                         }
                     }
+                    let feed_contracts = providers
+                        .iter()
+                        .filter_map(|(provider_index, _)| connected.get(&(chain_index, *provider_index, *address)));
+                    let fetches = feed_contracts
+                        .map(|contract| catch_panic(retries.run(identifier, || fetch_round(contract))));
+                    results.extend(join_all(fetches).await);
+
+                    let result = match configuration.rpc_aggregation {
+                        Some(aggregation) if providers.len() > 1 => aggregate(results, aggregation, identifier),
+                        _ => results.pop().unwrap_or(Err(Error::NotFound)),
+                    };
+                    match result {
+                        Err(error) if failovers > 0 && is_unreachable(&error) => {
+                            log::warn!("Fetching {} failed: {}", identifier, error);
+                            rustlink.fail_over();
+                            failovers -= 1;
+                        }
+                        result => break result,
+                    }
+                };
+                rustlink.record_fetch(&feed, result.is_ok());
+                if let (Ok(_), Some(timestamp)) = (&result, timestamp) {
+                    timestamps.insert((chain_index, *address), timestamp);
+                }
+                // A timestamp beyond u64 is bogus rather than old, so it is not checked
+                let last_update = result.as_ref().ok().and_then(|round| u64::try_from(round.updated_at).ok());
+                if let (Some(last_update), Some(freeze_threshold)) = (last_update, configuration.freeze_threshold) {
+                    let since = Duration::from_millis(configuration.clock.now_millis())
+                        .saturating_sub(Duration::from_secs(last_update));
+                    if since > freeze_threshold && frozen.insert((chain_index, *address), last_update) != Some(last_update) {
+                        log::warn!("{} was last updated {:?} ago", identifier, since);
+                        let event = FetchEvent::FeedFrozen { identifier: identifier.clone(), last_update, since };
+                        reflect_event(&rustlink.reflector, event).await;
+                    }
+                }
+                pass_succeeded |= result.is_ok();
+                match result
+                {
+                    Ok(price_data) if price_data.answered_in_round < price_data.round_id => {
+                        log::warn!("Withholding stale round {} of {}", price_data.round_id, identifier);
+                        let event = FetchEvent::StaleRound {
+                            identifier: price_data.identifier,
+                            round_id: price_data.round_id,
+                            answered_in_round: price_data.answered_in_round,
+                        };
+                        reflect_event(&rustlink.reflector, event).await;
+                    }
+                    Ok(price_data) if is_outlier(&configuration.sanity_ranges, chain_index, &price_data) => {
+                        log::warn!("Rejecting answer {} of {} outside of its sanity range", price_data.answer, identifier);
+                        reflect_event(&rustlink.reflector, FetchEvent::OutlierRejected(price_data)).await;
+                    }
+                    Ok(price_data) if dedup.as_mut().is_some_and(|dedup| !dedup.insert(&feed, &price_data)) => {
+                        log::debug!("Skipping already emitted round {} of {}", price_data.round_id, identifier);
+                    }
+                    Ok(mut price_data) => {
+                        if let Some(smoothing) = smoothing.as_mut() {
+                            smoothing.apply(&feed, &mut price_data);
+                        }
+                        velocity.apply(&feed, &mut price_data);
+                        previous.apply(&feed, &mut price_data);
+                        price_data.quote = match configuration.quotes.get(&feed) {
+                            Some(quote) => Some(quote.clone()),
+                            None if configuration.quotes_from_description => quote_of(&price_data.description),
+                            None => None,
+                        };
+                        if configuration.exact_answer {
+                            price_data.exact_answer = Some(price_data.answer_string());
+                        }
+                        price_data.seq = rustlink.seq.fetch_add(1, Ordering::Relaxed);
+                        for subscriber in rustlink.subscribers(&feed) {
+                            // A failure only means the subscriber went away in the meantime
+                            let _ = subscriber.send(price_data.clone()).await;
+                        }
+                        match rustlink.reflector {
+                            BatchSender(_) => batch.push(price_data),
+                            _ => reflect_round(&rustlink.reflector, price_data).await,
+                        }
+                        emitted_rounds += 1;
+                    }
                     Err(error) => {
                         log::error!("Failed updating price: {}", error);
                     }
                 }
+                if batch.is_due(false) {
+                    reflect_batch(&rustlink.reflector, batch.take()).await;
+                }
+                if configuration.max_rounds.is_some_and(|max_rounds| emitted_rounds >= max_rounds) {
+                    break 'fetching;
+                }
             }
+        }
+        warmup = false;
+        passes += 1;
+
+        failed_passes = if pass_succeeded { 0 } else { failed_passes + 1 };
+        if let Some(error_budget) = configuration.error_budget.filter(|budget| failed_passes >= budget.max_failed_passes) {
+            log::error!("Every fetch failed for {} consecutive passes, giving up", failed_passes);
+            reflect_event(&rustlink.reflector, FetchEvent::GivingUp { failed_passes }).await;
+            let Some(cooldown) = error_budget.cooldown else {
+                break 'fetching;
+            };
+            select! {
+                _ = shutdown_future => break 'fetching,
+                _ = workflow_rs::core::task::sleep(cooldown).fuse() => failed_passes = 0,
+            }
+        }
+
+        if batch.is_due(true) {
+            reflect_batch(&rustlink.reflector, batch.take()).await;
+        }
+
+        // Let supervisors know the loop is alive, even if every fetch of this pass failed.
+        if configuration.tick_events {
+            let at = configuration.clock.now_millis();
+            reflect_event(&rustlink.reflector, FetchEvent::Tick { at }).await;
+        }
+    }
+
+    reflect_batch(&rustlink.reflector, batch.take()).await;
+    close(&rustlink).await;
+    rustlink.running.store(false, Ordering::SeqCst);
+    rustlink.shutdown_send.send(()).await.unwrap();
+}
+
+/// Lets consumers know the loop has ended: round channels and subscriptions are closed,
+/// so that pending `recv` calls fail instead of waiting forever, and event consumers are
+/// sent `FetchEvent::Closed`. Event channels are left open for a later `start`.
+async fn close<M: Middleware + Clone>(rustlink: &Rustlink<M>) {
+    match &rustlink.reflector {
+        Sender(sender) => {
+            sender.close();
+        }
+        BatchSender(sender) => {
+            sender.close();
+        }
+        Events(_) => reflect_event(&rustlink.reflector, FetchEvent::Closed).await,
+        Custom(_) | Stdout | Callback(_) => {}
+        // Receivers see the channel closed once every clone of the sender is dropped
+        #[cfg(not(target_arch = "wasm32"))]
+        Broadcast(_) => {}
+    }
+    rustlink.subscribers.lock().unwrap().clear();
+}
+
+/// Turns a panic while fetching into an error for that fetch only, so that a bug
+/// triggered by one feed does not take the whole fetch loop down.
+async fn catch_panic<T>(fetch: impl Future<Output = Result<T, Error>>) -> Result<T, Error> {
+    AssertUnwindSafe(fetch).catch_unwind().await.unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(Error::Panicked(message))
+    })
+}
+
+/// Whether the answer of the round, fetched from the chain with index `chain_index`, lies
+/// outside of the sanity range of its feed, if any.
+fn is_outlier(sanity_ranges: &HashMap<FeedKey, (f64, f64)>, chain_index: usize, round: &Round) -> bool {
+    sanity_ranges
+        .get(&(chain_index, round.identifier.clone()))
+        .is_some_and(|&(min, max)| !(min..=max).contains(&round.answer))
+}
+
+/// Delivers a round to the configured reflector.
+async fn reflect_round(reflector: &Reflector, round: Round) {
+    let result = match reflector {
+        // Attempt to send the PriceData through the channel.
+        Sender(sender) => sender.send(round).await.map_err(|error| error.to_string()),
+        Events(sender) => sender
+            .send(FetchEvent::Round(round))
+            .await
+            .map_err(|error| error.to_string()),
+        BatchSender(sender) => sender.send(vec![round]).await.map_err(|error| error.to_string()),
+        Custom(sink) => {
+            sink.emit(round).await;
+            Ok(())
+        }
+        Stdout => serde_json::to_string(&round).map(print_line).map_err(|error| error.to_string()),
+        Callback(callback) => {
+            callback(round);
+            Ok(())
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Broadcast(sender) => {
+            // Sending only fails while nobody is subscribed, and the round is meant to be dropped then
+            if sender.send(round).is_err() {
+                log::trace!("Dropping a round broadcast while nobody is subscribed");
             }
+            Ok(())
+        }
+    };
+    if let Err(error) = result {
+        log::error!("Failed sending data: {}", error);
+    }
+}
+
+/// Prints a line for `Reflector::Stdout`, to the console on WASM.
+fn print_line(line: String) {
+    #[cfg(not(target_arch = "wasm32"))]
+    println!("{}", line);
+
+    #[cfg(target_arch = "wasm32")]
+    web_sys::console::log_1(&line.into());
+}
+
+/// Delivers the coalesced rounds of a batch to a `Reflector::BatchSender`.
+async fn reflect_batch(reflector: &Reflector, rounds: Vec<Round>) {
+    if let (BatchSender(sender), false) = (reflector, rounds.is_empty()) {
+        if let Err(error) = sender.send(rounds).await {
+            log::error!("Failed sending data: {}", error);
+        }
+    }
+}
+
+/// Delivers an event to the configured reflector, if it accepts events.
+async fn reflect_event(reflector: &Reflector, event: FetchEvent) {
+    if let Events(sender) = reflector {
+        if let Err(error) = sender.send(event).await {
+            log::error!("Failed sending event: {}", error);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_std::channel::unbounded;
+    use ethers::providers::Provider;
+
+    use super::{catch_panic, start_delay};
+    use crate::clock::MockClock;
+    use crate::core::{Reflector, Round, RustlinkBuilder};
+    use crate::error::Error;
+
+    #[tokio::test]
+    async fn panics_become_fetch_errors() {
+        let result: Result<Round, Error> = catch_panic(async { panic!("bad contract") }).await;
+        assert!(matches!(result, Err(Error::Panicked(message)) if message == "bad contract"));
+
+        let result = catch_panic(async { Ok(Round::default()) }).await;
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn start_delays_stay_within_the_interval() {
+        let (sender, _receiver) = unbounded();
+        let rustlink = RustlinkBuilder::default()
+            .with_fetch_interval_seconds(2)
+            .with_stagger_start(true)
+            .build_with_provider(Provider::mocked().0, Reflector::Sender(sender.clone()))
+            .unwrap();
+        for _ in 0..100 {
+            assert!(start_delay(&rustlink.configuration) < Duration::from_secs(2));
+        }
+
+        // Aligned to the next multiple of the interval, then shifted by the phase offset
+        let clock = Arc::new(MockClock::new(10_500));
+        let rustlink = RustlinkBuilder::default()
+            .with_fetch_interval_seconds(2)
+            .with_align_to_interval(true)
+            .with_phase_offset(Duration::from_millis(100))
+            .with_clock(clock)
+            .build_with_provider(Provider::mocked().0, Reflector::Sender(sender))
+            .unwrap();
+        assert_eq!(start_delay(&rustlink.configuration), Duration::from_millis(1_600));
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use crate::core::{FeedKey, Round};
+
+/// Remembers the last round emitted for every feed and fills in `Round::previous_answer`
+/// and `Round::previous_round_id`.
+pub struct Previous {
+    rounds: HashMap<FeedKey, (f64, u128)>,
+}
+
+impl Previous {
+    pub fn new() -> Self {
+        Previous {
+            rounds: HashMap::new(),
+        }
+    }
+
+    /// Stamps the answer and round id of the previous round of `feed` on the round,
+    /// then remembers the round for the next one. Both stay `None` for the first round of a feed.
+    pub fn apply(&mut self, feed: &FeedKey, round: &mut Round) {
+        let previous = self.rounds.insert(feed.clone(), (round.answer, round.round_id));
+        round.previous_answer = previous.map(|(answer, _)| answer);
+        round.previous_round_id = previous.map(|(_, round_id)| round_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Previous;
+    use crate::core::Round;
+
+    fn previous(previous: &mut Previous, round_id: u128, answer: f64) -> (Option<f64>, Option<u128>) {
+        let mut round = Round {
+            identifier: "ETH".to_string(),
+            round_id,
+            answer,
+            ..Default::default()
+        };
+        previous.apply(&(0, "ETH".to_string()), &mut round);
+        (round.previous_answer, round.previous_round_id)
+    }
+
+    #[test]
+    fn previous_round_of_the_feed() {
+        let mut state = Previous::new();
+        assert_eq!(previous(&mut state, 7, 100.0), (None, None));
+        assert_eq!(previous(&mut state, 8, 110.0), (Some(100.0), Some(7)));
+        assert_eq!(previous(&mut state, 9, 90.0), (Some(110.0), Some(8)));
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use super::reflect_round;
+use crate::core::{Reflector, Round};
+
+/// Emits a predefined sequence of rounds through a reflector, as if they had been
+/// fetched from a live chain. Create one with `Rustlink::replay`.
+#[derive(Clone)]
+pub struct ReplaySource {
+    rounds: Vec<Round>,
+    interval: Option<Duration>,
+    reflector: Reflector,
+}
+
+impl ReplaySource {
+    pub(crate) fn new(rounds: Vec<Round>, interval: Option<Duration>, reflector: Reflector) -> Self {
+        ReplaySource {
+            rounds,
+            interval,
+            reflector,
+        }
+    }
+
+    /// Emits every round in order, waiting `interval` before each one when set.
+    /// Returns once the last round has been delivered.
+    pub async fn run(self) {
+        for round in self.rounds {
+            if let Some(interval) = self.interval {
+                workflow_rs::core::task::sleep(interval).await;
+            }
+            reflect_round(&self.reflector, round).await;
+        }
+    }
+
+    /// Runs the replay in the background.
+    pub fn start(self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::task::spawn(self.run());
+
+        #[cfg(target_arch = "wasm32")]
+        wasm_bindgen_futures::spawn_local(self.run());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_std::channel::unbounded;
+
+    use crate::core::{FetchEvent, Reflector, Round, Rustlink};
+
+    fn round(round_id: u128) -> Round {
+        Round {
+            identifier: "ETH".to_string(),
+            chain_id: 56,
+            round_id,
+            answered_in_round: round_id,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_rounds_in_order() {
+        let (sender, receiver) = unbounded();
+        Rustlink::replay(vec![round(1), round(2)], None, Reflector::Sender(sender))
+            .run()
+            .await;
+
+        assert_eq!(receiver.recv().await.unwrap().round_id, 1);
+        assert_eq!(receiver.recv().await.unwrap().round_id, 2);
+        assert!(receiver.is_empty());
+    }
+
+    #[tokio::test]
+    async fn replays_on_a_schedule() {
+        let (sender, receiver) = unbounded();
+        let interval = Some(Duration::from_millis(10));
+        Rustlink::replay(vec![round(3)], interval, Reflector::Events(sender)).start();
+
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::Round(round) if round.round_id == 3));
+    }
+}
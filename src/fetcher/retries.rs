@@ -0,0 +1,141 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::clock::Clock;
+use crate::core::RetryBudget;
+use crate::error::Error;
+
+/// Retries the failed calls of the fetch loop, within a budget shared by every feed.
+pub struct Retries {
+    max_retries: u32,
+    base_delay: Duration,
+    budget: Option<Mutex<TokenBucket>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Retries {
+    pub fn new(max_retries: u32, base_delay: Duration, budget: Option<RetryBudget>, clock: Arc<dyn Clock>) -> Self {
+        let budget = budget.map(|budget| Mutex::new(TokenBucket::new(budget, clock.now_millis())));
+        Retries {
+            max_retries,
+            base_delay,
+            budget,
+            clock,
+        }
+    }
+
+    /// Runs `attempt` for the feed `identifier`, retrying failures that may not happen again up to
+    /// `max_retries` times while the budget allows. The delay before a retry starts at `base_delay`
+    /// and doubles every attempt.
+    pub async fn run<T, F: Future<Output = Result<T, Error>>>(
+        &self,
+        identifier: &str,
+        mut attempt: impl FnMut() -> F,
+    ) -> Result<T, Error> {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(error) if retry < self.max_retries && is_transient(&error) && self.take() => {
+                    let delay = self.base_delay.saturating_mul(2u32.saturating_pow(retry));
+                    log::warn!("Fetching {} failed, retrying in {:?}: {}", identifier, delay, error);
+                    workflow_rs::core::task::sleep(delay).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Takes a retry from the budget, if there is one left.
+    fn take(&self) -> bool {
+        let Some(budget) = &self.budget else {
+            return true;
+        };
+        let taken = budget.lock().unwrap().take(self.clock.now_millis());
+        if !taken {
+            log::warn!("Retry budget exhausted, not retrying");
+        }
+        taken
+    }
+}
+
+/// Whether a failed call may succeed when tried again: the provider could not be reached, did
+/// not answer in time or is rate limiting. Reverts and malformed answers fail the same way every time.
+fn is_transient(error: &Error) -> bool {
+    matches!(error, Error::Connection(_) | Error::Timeout | Error::RateLimited(_))
+}
+
+/// The retries left in a `RetryBudget`, refilled continuously.
+struct TokenBucket {
+    budget: RetryBudget,
+    tokens: f64,
+    refilled_at: u64,
+}
+
+impl TokenBucket {
+    fn new(budget: RetryBudget, now_millis: u64) -> Self {
+        TokenBucket {
+            budget,
+            tokens: budget.retries.into(),
+            refilled_at: now_millis,
+        }
+    }
+
+    /// Takes a token at `now_millis`, after adding those refilled since the last call.
+    fn take(&mut self, now_millis: u64) -> bool {
+        let capacity = f64::from(self.budget.retries);
+        let elapsed = now_millis.saturating_sub(self.refilled_at) as f64;
+        let period = self.budget.per.as_millis().max(1) as f64;
+        self.tokens = (self.tokens + elapsed * capacity / period).min(capacity);
+        self.refilled_at = now_millis;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::{Retries, TokenBucket};
+    use crate::clock::MockClock;
+    use crate::core::RetryBudget;
+    use crate::error::Error;
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let mut bucket = TokenBucket::new(RetryBudget { retries: 2, per: Duration::from_secs(1) }, 0);
+        assert!(bucket.take(0));
+        assert!(bucket.take(0));
+        assert!(!bucket.take(0));
+        assert!(bucket.take(500));
+        assert!(!bucket.take(500));
+        // Never more than the budget, however long it has been
+        assert!(bucket.take(3_600_000));
+        assert!(bucket.take(3_600_000));
+        assert!(!bucket.take(3_600_000));
+    }
+
+    #[tokio::test]
+    async fn exhausted_budgets_pass_failures_through() {
+        let budget = RetryBudget { retries: 1, per: Duration::from_secs(3600) };
+        let retries = Retries::new(3, Duration::ZERO, Some(budget), Arc::new(MockClock::new(0)));
+        let attempts = AtomicU32::new(0);
+        let attempt = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(Error::Timeout)
+        };
+
+        assert!(matches!(retries.run("ETH", attempt).await, Err(Error::Timeout)));
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 2);
+        assert!(matches!(retries.run("ETH", attempt).await, Err(Error::Timeout)));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::core::{FeedKey, Round, SmoothingConfig};
+
+/// Keeps the smoothing state of every feed and fills in `Round::smoothed_answer`.
+pub struct Smoothing {
+    config: SmoothingConfig,
+    feeds: HashMap<FeedKey, FeedSmoothing>,
+}
+
+/// The smoothing state of a single feed.
+enum FeedSmoothing {
+    MovingAverage(VecDeque<f64>),
+    Exponential(f64),
+}
+
+impl Smoothing {
+    pub fn new(config: SmoothingConfig) -> Self {
+        Smoothing {
+            config,
+            feeds: HashMap::new(),
+        }
+    }
+
+    /// Adds the answer of the round to the state of `feed` and stamps the smoothed answer on it.
+    pub fn apply(&mut self, feed: &FeedKey, round: &mut Round) {
+        let smoothed = match (self.config, self.feeds.get_mut(feed)) {
+            (SmoothingConfig::MovingAverage(samples), Some(FeedSmoothing::MovingAverage(answers))) => {
+                answers.push_back(round.answer);
+                if answers.len() > samples.max(1) {
+                    answers.pop_front();
+                }
+                answers.iter().sum::<f64>() / answers.len() as f64
+            }
+            (SmoothingConfig::Exponential(alpha), Some(FeedSmoothing::Exponential(average))) => {
+                *average += alpha * (round.answer - *average);
+                *average
+            }
+            // First answer of this feed
+            (SmoothingConfig::MovingAverage(_), _) => {
+                self.feeds.insert(feed.clone(), FeedSmoothing::MovingAverage(VecDeque::from([round.answer])));
+                round.answer
+            }
+            (SmoothingConfig::Exponential(_), _) => {
+                self.feeds.insert(feed.clone(), FeedSmoothing::Exponential(round.answer));
+                round.answer
+            }
+        };
+        round.smoothed_answer = Some(smoothed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Smoothing;
+    use crate::core::{Round, SmoothingConfig};
+
+    fn smooth(smoothing: &mut Smoothing, answer: f64) -> Option<f64> {
+        let mut round = Round {
+            identifier: "ETH".to_string(),
+            answer,
+            ..Default::default()
+        };
+        smoothing.apply(&(0, "ETH".to_string()), &mut round);
+        round.smoothed_answer
+    }
+
+    #[test]
+    fn moving_average() {
+        let mut smoothing = Smoothing::new(SmoothingConfig::MovingAverage(2));
+        assert_eq!(smooth(&mut smoothing, 10.0), Some(10.0));
+        assert_eq!(smooth(&mut smoothing, 20.0), Some(15.0));
+        assert_eq!(smooth(&mut smoothing, 40.0), Some(30.0));
+    }
+
+    #[test]
+    fn exponential_average() {
+        let mut smoothing = Smoothing::new(SmoothingConfig::Exponential(0.5));
+        assert_eq!(smooth(&mut smoothing, 10.0), Some(10.0));
+        assert_eq!(smooth(&mut smoothing, 20.0), Some(15.0));
+        assert_eq!(smooth(&mut smoothing, 15.0), Some(15.0));
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use crate::core::{FeedKey, Round};
+
+/// Remembers the previous answer of every feed and fills in `Round::velocity`.
+pub struct Velocity {
+    previous: HashMap<FeedKey, (f64, u64)>,
+}
+
+impl Velocity {
+    pub fn new() -> Self {
+        Velocity {
+            previous: HashMap::new(),
+        }
+    }
+
+    /// Stamps the change per second since the previous round of `feed` on the round.
+    /// The velocity stays `None` for the first round of a feed and when `updated_at`
+    /// did not move forward, as there is no elapsed time to divide by, and for rounds whose
+    /// `updated_at` is beyond `u64`, which are left out of the state of the feed.
+    pub fn apply(&mut self, feed: &FeedKey, round: &mut Round) {
+        round.velocity = None;
+        let Ok(updated_at) = u64::try_from(round.updated_at) else {
+            return;
+        };
+        match self.previous.get(feed) {
+            Some(&(_, previous_updated_at)) if updated_at <= previous_updated_at => return,
+            Some(&(answer, previous_updated_at)) => {
+                round.velocity = Some((round.answer - answer) / (updated_at - previous_updated_at) as f64);
+            }
+            None => {}
+        }
+        self.previous.insert(feed.clone(), (round.answer, updated_at));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+
+    use super::Velocity;
+    use crate::core::Round;
+
+    fn velocity(velocity: &mut Velocity, answer: f64, updated_at: impl Into<U256>) -> Option<f64> {
+        let mut round = Round {
+            identifier: "ETH".to_string(),
+            answer,
+            updated_at: updated_at.into(),
+            ..Default::default()
+        };
+        velocity.apply(&(0, "ETH".to_string()), &mut round);
+        round.velocity
+    }
+
+    #[test]
+    fn change_per_second() {
+        let mut state = Velocity::new();
+        assert_eq!(velocity(&mut state, 100.0, 1000), None);
+        assert_eq!(velocity(&mut state, 110.0, 1010), Some(1.0));
+        // The same round fetched again has no elapsed time
+        assert_eq!(velocity(&mut state, 110.0, 1010), None);
+        assert_eq!(velocity(&mut state, 90.0, 1020), Some(-2.0));
+        // A bogus timestamp beyond u64 neither gets a velocity nor replaces the previous round
+        assert_eq!(velocity(&mut state, 50.0, U256::MAX), None);
+        assert_eq!(velocity(&mut state, 100.0, 1030), Some(1.0));
+    }
+}
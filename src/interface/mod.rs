@@ -1,22 +1,139 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use serde::{Deserialize, Serialize};
-use ethers::{abi::{Abi, AbiError}, contract::Contract, providers::{Http, Provider}, types::{Address, U256}};
+use crate::error::Error;
+use futures::future::join_all;
+use ethers::{abi::{decode, Abi, Detokenize, ParamType, Token}, contract::{Contract, ContractCall, ContractError}, providers::{Http, Middleware, MiddlewareError, Provider}, types::{Address, I256, U256}, utils::to_checksum};
 
+/// Number of past rounds inspected by `ChainlinkContract::suggested_interval`
+const SUGGESTED_INTERVAL_ROUNDS: usize = 5;
 
+/// The tuple returned by `latestRoundData` and `getRoundData`:
+/// round id, answer, started at, updated at and answered in round.
+pub type RoundData = (u128, I256, U256, U256, u128);
+
+/// Selector of the EIP-3668 `OffchainLookup(address,string[],bytes,bytes4,bytes)` error.
+const OFFCHAIN_LOOKUP_SELECTOR: [u8; 4] = [0x55, 0x6f, 0x18, 0x30];
+
+/// How long a contract call may take before failing with `Error::Timeout`, unless configured otherwise.
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Converts a failed contract call of the feed `identifier` into an error, telling reverts
+/// asking for an off-chain lookup, other reverts, rate limits and providers that could not be
+/// reached apart from every other failure.
+fn call_error<M: Middleware>(identifier: &str) -> impl FnOnce(ContractError<M>) -> Error + '_ {
+    move |error| {
+        let offchain_lookup = error
+            .as_revert()
+            .and_then(|revert| revert.strip_prefix(&OFFCHAIN_LOOKUP_SELECTOR))
+            .and_then(|arguments| {
+                let types = [
+                    ParamType::Address,
+                    ParamType::Array(Box::new(ParamType::String)),
+                    ParamType::Bytes,
+                    ParamType::FixedBytes(4),
+                    ParamType::Bytes,
+                ];
+                decode(&types, arguments).ok()
+            });
+
+        match offchain_lookup.as_deref() {
+            Some([_, Token::Array(urls), ..]) => Error::OffchainLookup(
+                urls.iter().filter_map(|url| url.clone().into_string()).collect(),
+            ),
+            _ if error.is_revert() => Error::ContractReverted {
+                identifier: identifier.to_string(),
+            },
+            _ if error.as_middleware_error().is_some_and(is_rate_limited) => Error::RateLimited(error.to_string()),
+            _ if error.as_middleware_error().is_some_and(is_unreachable) => Error::Connection(error.to_string()),
+            _ => Error::Call(error.to_string()),
+        }
+    }
+}
+
+/// Whether a request failed without the provider answering it, neither with a result nor
+/// with a JSON-RPC error.
+fn is_unreachable<E: MiddlewareError>(error: &E) -> bool {
+    error.as_error_response().is_none() && error.as_serde_error().is_none()
+}
+
+/// Whether the provider answered with a JSON-RPC error telling that too many requests were made,
+/// using the codes of the common providers or, failing that, the message.
+fn is_rate_limited<E: MiddlewareError>(error: &E) -> bool {
+    error.as_error_response().is_some_and(|response| {
+        matches!(response.code, 429 | -32005 | -32029) || response.message.to_lowercase().contains("rate limit")
+    })
+}
+
+/// Converts an answer as reported by an aggregator into a human-readable one.
+pub(crate) fn scale(answer: I256, decimals: u8) -> Result<f64, Error> {
+    let answer: f64 = answer.to_string().parse().map_err(|_| Error::Deserialize)?;
+    Ok(answer / 10f64.powi(decimals.into()))
+}
+
+/// Returns `10^exponent`, or `None` when it does not fit in a `U256`.
+fn pow10(exponent: u32) -> Option<U256> {
+    U256::from(10).checked_pow(U256::from(exponent))
+}
+
+/// Makes a contract call of the feed `identifier`, failing with `Error::Timeout` when the
+/// provider does not answer within `timeout`.
+async fn call<M: Middleware, D: Detokenize>(call: ContractCall<M, D>, identifier: &str, timeout: Duration) -> Result<D, Error> {
+    crate::core::before(
+        async { call.call().await.map_err(call_error(identifier)) },
+        workflow_rs::core::task::sleep(timeout),
+    )
+    .await
+}
+
+/// A Chainlink price aggregator contract, bound to the provider of its chain.
 #[derive(Clone)]
-pub struct ChainlinkContract<'a> {
-    pub contract: Contract<&'a Provider<Http>>,
+pub struct ChainlinkContract<'a, M: Middleware = Provider<Http>> {
+    pub contract: Contract<M>,
     pub identifier: &'a str,
     pub decimals: u8,
+    /// Description of the aggregator, e.g. `ETH / USD`
+    pub description: String,
+    pub chain_id: u64,
+    /// Address the rounds are read from, for access controlled feeds
+    pub call_from: Option<Address>,
+    /// How long each contract call may take
+    pub call_timeout: Duration,
+}
+
+
+/// Returns the quote currency of a feed description, e.g. `USD` for `ETH / USD`.
+pub fn quote_of(description: &str) -> Option<String> {
+    let (_, quote) = description.rsplit_once('/')?;
+    Some(quote.trim().to_string()).filter(|quote| !quote.is_empty())
 }
 
+/// Serde of `Round::raw_answer` as a decimal string, which JSON consumers such as JS can parse
+/// without overflowing their numbers. Hex strings, as written by earlier versions, are still read.
+mod decimal_string {
+    use ethers::types::{I256, U256};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &I256, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<I256, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        match value.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).map(I256::from_raw).map_err(Error::custom),
+            None => I256::from_dec_str(&value).map_err(Error::custom),
+        }
+    }
+}
 
 /// The latest price received for this symbol.
 /// This data is directly retrieved from the underlying contract.
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Round {
     // Identifier of the underlying asset
     pub identifier: String,
+    /// Id of the EVM chain the aggregator lives on, either configured or queried via `eth_chainId`
+    pub chain_id: u64,
     /// Id of the submission by the aggregator
     pub round_id: u128,
     /// Answered in round
@@ -27,57 +144,497 @@ pub struct Round {
     pub updated_at: U256,
     /// Answer of this round         
     pub answer: f64,
+    /// Undivided answer exactly as reported by the aggregator, serialized as a decimal string
+    #[serde(with = "decimal_string")]
+    pub raw_answer: I256,
+    /// Number of decimals the aggregator reports its answer in
+    pub decimals: u8,
+    /// Moving average of the answers of this feed, when smoothing is configured
+    #[serde(default)]
+    pub smoothed_answer: Option<f64>,
+    /// Change of the answer per second since the previous round of this feed,
+    /// `None` for the first round observed
+    #[serde(default)]
+    pub velocity: Option<f64>,
+    /// Answer of the round emitted before this one for the same feed, `None` for the first
+    #[serde(default)]
+    pub previous_answer: Option<f64>,
+    /// Id of the round emitted before this one for the same feed, `None` for the first
+    #[serde(default)]
+    pub previous_round_id: Option<u128>,
+    /// Currency the answer is quoted in, e.g. `USD`, when configured or taken from the description
+    #[serde(default)]
+    pub quote: Option<String>,
+    /// Description of the aggregator as reported by its `description()`, e.g. `ETH / USD`.
+    /// Empty for legacy aggregators without one.
+    #[serde(default)]
+    pub description: String,
+    /// The answer as returned by `answer_string`, when `exact_answer` is configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exact_answer: Option<String>,
+    /// Local sequence number, incremented for every round emitted by a Rustlink instance
+    /// across all of its feeds. Gives a total order that per-feed round ids can't provide.
+    #[serde(default)]
+    pub seq: u64,
 }
 
-impl<'a> ChainlinkContract<'a> {
+impl Round {
+    /// The header matching the columns of `to_csv_record`.
+    pub fn csv_header() -> &'static str {
+        "identifier,chain_id,round_id,answer,raw_answer,decimals,updated_at"
+    }
+
+    /// Formats this round as a CSV row, in the column order of `csv_header`.
+    /// The identifier is quoted when it contains a comma, a quote or a line break.
+    pub fn to_csv_record(&self) -> String {
+        let identifier = if self.identifier.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", self.identifier.replace('"', "\"\""))
+        } else {
+            self.identifier.clone()
+        };
+
+        format!(
+            "{},{},{},{},{},{},{}",
+            identifier,
+            self.chain_id,
+            self.round_id,
+            self.answer,
+            self.raw_answer,
+            self.decimals,
+            self.updated_at
+        )
+    }
+
+    /// Extracts the phase from a proxy round id. Proxies pack the phase of the underlying
+    /// aggregator in the bits above the low 64, which increments on every aggregator upgrade.
+    pub fn phase_id(round_id: u128) -> u16 {
+        (round_id >> 64) as u16
+    }
+
+    /// Extracts the round id of the underlying aggregator from a proxy round id,
+    /// stored in its low 64 bits. Rounds restart from 1 in every phase.
+    pub fn aggregator_round_id(round_id: u128) -> u64 {
+        round_id as u64
+    }
+
+    /// Returns the answer rounded half away from zero to `places` decimals. The rounding is
+    /// done on `raw_answer` before converting to `f64`, so `2431.555` rounded to 2 places
+    /// is `2431.56` rather than whatever its closest binary representation rounds to.
+    pub fn answer_rounded(&self, places: u32) -> f64 {
+        let (sign, abs) = self.raw_answer.into_sign_and_abs();
+        let decimals = self.decimals as u32;
+        let (rounded, places) = match decimals.checked_sub(places) {
+            Some(dropped) if dropped > 0 => match pow10(dropped) {
+                // Rounding up when the remainder is at least half the divisor, without overflowing
+                Some(divisor) => {
+                    let (quotient, remainder) = abs.div_mod(divisor);
+                    match remainder >= divisor - remainder {
+                        true => (quotient + 1, places),
+                        false => (quotient, places),
+                    }
+                }
+                // Dividing by more than any U256 rounds every answer to zero
+                None => (U256::zero(), places),
+            },
+            _ => (abs, decimals),
+        };
+
+        // Parsing the decimal representation yields the closest f64
+        let digits = format!("{:0>width$}", rounded.to_string(), width = places as usize + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - places as usize);
+        let answer: f64 = format!("{}.{}", integer, fraction).parse().unwrap_or(f64::NAN);
+        if sign.is_negative() {
+            -answer
+        } else {
+            answer
+        }
+    }
+
+    /// Returns the answer in cents, rounded half up using exact integer math. `None` when the
+    /// round is quoted in another currency than USD, when the answer is negative or when it
+    /// does not fit in a `u64`. Rounds of unknown quote are assumed to be quoted in USD.
+    pub fn answer_usd_cents(&self) -> Option<u64> {
+        if self.quote.as_deref().is_some_and(|quote| !quote.eq_ignore_ascii_case("USD")) {
+            return None;
+        }
+        let (sign, abs) = self.raw_answer.into_sign_and_abs();
+        if sign.is_negative() {
+            return None;
+        }
+
+        let decimals = self.decimals as u32;
+        let cents = match decimals.checked_sub(2) {
+            // Dividing by more than any U256 rounds every answer to zero
+            Some(dropped) => match pow10(dropped) {
+                Some(divisor) => abs.checked_add(divisor / 2)? / divisor,
+                None => U256::zero(),
+            },
+            None => abs.checked_mul(pow10(2 - decimals)?)?,
+        };
+        u64::try_from(cents).ok()
+    }
+
+    /// Returns how far this answer is from the answer of `other`, in basis points of the
+    /// answer of `other`, rounded half away from zero. E.g. a stablecoin feed answering
+    /// `0.9950` against a `1.0000` round gives `-50`. Computed exactly on the raw answers,
+    /// whatever their decimals. `None` when either answer is zero or the result overflows.
+    pub fn spread_bps(&self, other: &Round) -> Option<i64> {
+        if self.raw_answer.is_zero() || other.raw_answer.is_zero() {
+            return None;
+        }
+
+        // Bring both answers to the same number of decimals
+        let decimals = self.decimals.max(other.decimals);
+        let scale = |round: &Round| {
+            let factor = I256::try_from(pow10((decimals - round.decimals).into())?).ok()?;
+            factor.checked_mul(round.raw_answer)
+        };
+        let (answer, reference) = (scale(self)?, scale(other)?);
+
+        let difference = answer.checked_sub(reference)?.checked_mul(I256::from(10_000))?;
+        let (quotient, remainder) = (difference / reference, difference % reference);
+        let rounding = match remainder.unsigned_abs() * 2 >= reference.unsigned_abs() {
+            true if difference.is_negative() != reference.is_negative() => I256::minus_one(),
+            true => I256::one(),
+            false => I256::zero(),
+        };
+        i64::try_from(quotient + rounding).ok()
+    }
+
+    /// Whether the answer moved by at least `min_bps` basis points of the answer of `prev`.
+    /// Computed exactly on the raw answers, whatever their decimals, so a move right at the
+    /// threshold counts. An unchanged answer never counts as a move, and any move away from
+    /// a zero answer always does.
+    pub fn changed_from(&self, prev: &Round, min_bps: u32) -> bool {
+        // Bring both answers to the same number of decimals, anything overflowing moved a lot
+        let decimals = self.decimals.max(prev.decimals);
+        let scale = |round: &Round| {
+            let factor = I256::try_from(pow10((decimals - round.decimals).into())?).ok()?;
+            factor.checked_mul(round.raw_answer)
+        };
+        let (Some(answer), Some(reference)) = (scale(self), scale(prev)) else {
+            return true;
+        };
+        let Some(difference) = answer.checked_sub(reference) else {
+            return true;
+        };
+        if difference.is_zero() {
+            return false;
+        }
+
+        let moved = difference.unsigned_abs().checked_mul(U256::from(10_000));
+        let threshold = reference.unsigned_abs().checked_mul(U256::from(min_bps));
+        match (moved, threshold) {
+            (Some(moved), Some(threshold)) => moved >= threshold,
+            (None, _) => true,
+            (_, None) => false,
+        }
+    }
+
+    /// Returns the answer as a decimal string with exactly `decimals` fractional digits,
+    /// derived from `raw_answer` without going through `f64`. An 8 decimal feed answering
+    /// `243155000000` yields `"2431.55000000"`.
+    pub fn answer_string(&self) -> String {
+        let (sign, abs) = self.raw_answer.into_sign_and_abs();
+        let decimals = self.decimals as usize;
+        let digits = format!("{:0>width$}", abs.to_string(), width = decimals + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - decimals);
+
+        let sign = if sign.is_negative() { "-" } else { "" };
+        match fraction.is_empty() {
+            true => format!("{}{}", sign, integer),
+            false => format!("{}{}.{}", sign, integer, fraction),
+        }
+    }
+
+    /// Returns the answer as an exact decimal string, derived from `raw_answer`
+    /// without going through `f64`. Trailing zeros are trimmed, so an 18 decimal
+    /// feed answering `1500000000000000000` yields `"1.5"`.
+    pub fn answer_decimal(&self) -> String {
+        let decimal = self.answer_string();
+        match decimal.contains('.') {
+            true => decimal.trim_end_matches('0').trim_end_matches('.').to_string(),
+            false => decimal,
+        }
+    }
+}
+
+impl<'a, M: Middleware + Clone> ChainlinkContract<'a, M> {
     /// Creates a new instance of a chainlink price aggregator. This is just a wrapper
-    /// function to simplify the interactions with the contract.
+    /// function to simplify the interactions with the contract. The decimals and the
+    /// description of the aggregator are queried once here and reused for every round.
     pub async fn new(
-        provider: &'a Provider<Http>,
+        provider: &'a M,
+        identifier: &'a str,
+        contract_address: Address,
+        chain_id: u64,
+    ) -> Result<ChainlinkContract<'a, M>, Error> {
+        Self::with_call_timeout(provider, identifier, contract_address, chain_id, None, DEFAULT_CALL_TIMEOUT).await
+    }
+
+    /// Like `new`, for an aggregator whose answers have `decimals` decimals, without querying
+    /// the aggregator for them.
+    pub async fn with_decimals(
+        provider: &'a M,
         identifier: &'a str,
         contract_address: Address,
-    ) -> Result<ChainlinkContract<'a>, AbiError> {
-        let abi:Abi=serde_json::from_str(include_str!("IAggregatorV3Interface.json")).unwrap();
-        let contract = Contract::new(contract_address, abi, Arc::new(provider));
+        chain_id: u64,
+        decimals: u8,
+    ) -> Result<ChainlinkContract<'a, M>, Error> {
+        Self::with_call_timeout(provider, identifier, contract_address, chain_id, Some(decimals), DEFAULT_CALL_TIMEOUT).await
+    }
 
-        let decimals=contract.method::<_,U256>("decimals", ()).unwrap()
-        .call().await.unwrap().as_u64() as u8;
+    /// Like `new`, failing every contract call that takes longer than `call_timeout` with
+    /// `Error::Timeout`. The decimals are only queried when not given. Legacy aggregators
+    /// without a description get an empty one.
+    pub async fn with_call_timeout(
+        provider: &'a M,
+        identifier: &'a str,
+        contract_address: Address,
+        chain_id: u64,
+        decimals: Option<u8>,
+        call_timeout: Duration,
+    ) -> Result<ChainlinkContract<'a, M>, Error> {
+        let contract = Self::aggregator(provider, contract_address)?;
+        let decimals = match decimals {
+            Some(decimals) => decimals,
+            None => Self::decimals(&contract, identifier, call_timeout).await?,
+        };
+        let description = call(contract.method::<_, String>("description", ())?, identifier, call_timeout).await;
+        let description = match description {
+            Ok(description) => description,
+            Err(Error::ContractReverted { .. }) => String::new(),
+            Err(error) => return Err(error),
+        };
 
         Ok(ChainlinkContract {
             contract,
             decimals,
+            description,
             identifier,
+            chain_id,
+            call_from: None,
+            call_timeout,
         })
     }
 
+    /// Makes the calls reading rounds from the given address. Some access controlled
+    /// feeds revert unless the caller is allowed to read them.
+    pub fn with_call_from(mut self, call_from: Address) -> Self {
+        self.call_from = Some(call_from);
+        self
+    }
+
+    /// Queries the number of decimals the aggregator at `contract_address` reports its answers in.
+    pub async fn fetch_decimals(provider: &M, contract_address: Address, call_timeout: Duration) -> Result<u8, Error> {
+        let identifier = to_checksum(&contract_address, None);
+        Self::decimals(&Self::aggregator(provider, contract_address)?, &identifier, call_timeout).await
+    }
+
+    /// Queries the description of the aggregator at `contract_address`, e.g. `ETH / USD`.
+    pub async fn fetch_description(provider: &M, contract_address: Address, call_timeout: Duration) -> Result<String, Error> {
+        let identifier = to_checksum(&contract_address, None);
+        call(Self::aggregator(provider, contract_address)?.method("description", ())?, &identifier, call_timeout).await
+    }
+
+    /// Queries the number of decimals of the aggregator of the feed `identifier`.
+    async fn decimals(contract: &Contract<M>, identifier: &str, call_timeout: Duration) -> Result<u8, Error> {
+        let decimals: U256 = call(contract.method("decimals", ())?, identifier, call_timeout).await?;
+
+        u8::try_from(decimals).map_err(|_| Error::Deserialize)
+    }
+
+    /// Binds the aggregator ABI to `contract_address`.
+    fn aggregator(provider: &M, contract_address: Address) -> Result<Contract<M>, Error> {
+        let abi: Abi = serde_json::from_str(include_str!("IAggregatorV3Interface.json"))
+            .map_err(|_| Error::Deserialize)?;
+        Ok(Contract::new(contract_address, abi, Arc::new(provider.clone())))
+    }
+
     /// Retrieves the latest price of this underlying asset
     /// from the chainlink decentralized data feed
-    pub async fn latest_round_data(&self) -> Result<Round, AbiError> {
-        let (round_id, answer, started_at, updated_at, answered_in_round): (
-            u128,
-            u128,
-            U256,
-            U256,
-            u128,
-        ) = self
-            .contract
-            .method("latestRoundData", ())?
-            .call()
-            .await.unwrap();
-
-        // Convert the answer on contract to a string.
-        let float_answer: f64 = answer.to_string().parse().unwrap();
+    pub async fn latest_round_data(&self) -> Result<Round, Error> {
+        self.to_round(self.latest_round_raw().await?)
+    }
+
+    /// Retrieves the values returned by `latestRoundData` exactly as the aggregator reported
+    /// them: round id, answer, started at, updated at and answered in round.
+    pub async fn latest_round_raw(&self) -> Result<RoundData, Error> {
+        let mut round_call = self.contract.method("latestRoundData", ())?;
+        if let Some(call_from) = self.call_from {
+            round_call = round_call.from(call_from);
+        }
+        call(round_call, self.identifier, self.call_timeout).await
+    }
+
+    /// Retrieves the latest answer alone through the legacy `latestAnswer` method, converted
+    /// with the decimals of the aggregator. Lighter than `latest_round_data`, but without the
+    /// round id and timestamps there is no way to tell how fresh the answer is.
+    pub async fn latest_answer(&self) -> Result<f64, Error> {
+        let mut answer_call = self.contract.method::<_, I256>("latestAnswer", ())?;
+        if let Some(call_from) = self.call_from {
+            answer_call = answer_call.from(call_from);
+        }
+        let answer = call(answer_call, self.identifier, self.call_timeout).await?;
+        scale(answer, self.decimals)
+    }
+
+    /// Retrieves when the aggregator was last updated through the legacy `latestTimestamp`
+    /// method. A single word instead of a whole round, which makes it a cheap way to tell
+    /// whether fetching `latestRoundData` is worthwhile.
+    pub async fn latest_timestamp(&self) -> Result<U256, Error> {
+        Self::timestamp(&self.contract, self.identifier, self.call_from, self.call_timeout).await
+    }
+
+    /// Queries the `latestTimestamp` of the aggregator at `contract_address`, without
+    /// querying its decimals first as `new` does.
+    pub async fn fetch_latest_timestamp(
+        provider: &M,
+        contract_address: Address,
+        call_from: Option<Address>,
+        call_timeout: Duration,
+    ) -> Result<U256, Error> {
+        let identifier = to_checksum(&contract_address, None);
+        Self::timestamp(&Self::aggregator(provider, contract_address)?, &identifier, call_from, call_timeout).await
+    }
+
+    /// Queries the `latestTimestamp` of the aggregator of the feed `identifier`.
+    async fn timestamp(
+        contract: &Contract<M>,
+        identifier: &str,
+        call_from: Option<Address>,
+        call_timeout: Duration,
+    ) -> Result<U256, Error> {
+        let mut timestamp_call = contract.method("latestTimestamp", ())?;
+        if let Some(call_from) = call_from {
+            timestamp_call = timestamp_call.from(call_from);
+        }
+        call(timestamp_call, identifier, call_timeout).await
+    }
+
+    /// Retrieves the latest round followed by up to `count` rounds before it, newest first.
+    /// The previous rounds are fetched concurrently. Rounds that cannot be retrieved are
+    /// skipped, and the walk stops at the first round of the current aggregator.
+    pub async fn recent_rounds(&self, count: usize) -> Result<Vec<Round>, Error> {
+        let latest = self.latest_round_data().await?;
+        let first = Round::aggregator_round_id(latest.round_id).saturating_sub(1).min(count as u64);
+
+        let previous = join_all((1..=first as u128).map(|back| self.round_data(latest.round_id - back))).await;
+
+        let mut rounds = Vec::with_capacity(previous.len() + 1);
+        rounds.push(latest);
+        rounds.extend(previous.into_iter().filter_map(Result::ok));
+        Ok(rounds)
+    }
+
+    /// Derives a polling interval from how often this feed actually updates, by looking at the
+    /// `updated_at` deltas of the last few rounds. Returns the median delta, or `None` when not
+    /// enough rounds could be retrieved. Useful to avoid over-polling feeds that update hourly.
+    pub async fn suggested_interval(&self) -> Option<Duration> {
+        let mut round = self.latest_round_data().await.ok()?;
+        let mut deltas = Vec::with_capacity(SUGGESTED_INTERVAL_ROUNDS);
+
+        // Stop walking back once the first round of the current aggregator is reached.
+        while deltas.len() < SUGGESTED_INTERVAL_ROUNDS && Round::aggregator_round_id(round.round_id) > 1 {
+            let Ok(previous) = self.round_data(round.round_id - 1).await else {
+                break;
+            };
+            // A delta beyond u64 can only come from a bogus timestamp, it tells nothing
+            if let Ok(delta) = u64::try_from(round.updated_at.saturating_sub(previous.updated_at)) {
+                deltas.push(delta);
+            }
+            round = previous;
+        }
+
+        if deltas.is_empty() {
+            return None;
+        }
+        deltas.sort_unstable();
+        Some(Duration::from_secs(deltas[deltas.len() / 2]))
+    }
+
+    /// Retrieves the answer of a specific round, scaled by the decimals of the feed.
+    /// Works with aggregators implementing either the V3 `getRoundData` or only the
+    /// legacy V2 `getAnswer` method.
+    pub async fn answer_at(&self, round_id: U256) -> Result<f64, Error> {
+        let round_id = u128::try_from(round_id)
+            .map_err(|_| Error::Call(format!("Round id {} is out of range", round_id)))?;
+        Ok(self.round_data(round_id).await?.answer)
+    }
+
+    /// Retrieves when a round was updated through the dedicated `getTimestamp` method,
+    /// falling back to the `updated_at` of `getRoundData` for contracts only implementing V3.
+    pub async fn round_timestamp(&self, round_id: u128) -> Result<U256, Error> {
+        let timestamp = self.contract.method::<_, U256>("getTimestamp", U256::from(round_id))?;
+        match call(timestamp, self.identifier, self.call_timeout).await {
+            Ok(timestamp) => return Ok(timestamp),
+            Err(Error::Timeout) => return Err(Error::Timeout),
+            Err(_) => {}
+        }
+
+        let round_data = self.contract.method("getRoundData", round_id)?;
+        let (_, _, _, updated_at, _): RoundData = call(round_data, self.identifier, self.call_timeout).await?;
+        Ok(updated_at)
+    }
+
+    /// Retrieves a past round of this aggregator through `getRoundData`, or the legacy V2
+    /// methods for aggregators without it, converted like `latest_round_data`. Fails with
+    /// `Error::RoundNotFound` when the aggregator has no data for the round, e.g. because it
+    /// predates the first round of the feed.
+    pub async fn historical_round_data(&self, round_id: u128) -> Result<Round, Error> {
+        if Round::aggregator_round_id(round_id) == 0 {
+            return Err(Error::RoundNotFound(round_id));
+        }
+        match self.round_data(round_id).await {
+            Ok(round) if round.updated_at.is_zero() => Err(Error::RoundNotFound(round_id)),
+            Err(Error::ContractReverted { .. }) => Err(Error::RoundNotFound(round_id)),
+            result => result,
+        }
+    }
+
+    /// Retrieves a specific round of this aggregator. Falls back to the legacy V2
+    /// `getAnswer` and `getTimestamp` methods when `getRoundData` is not implemented.
+    async fn round_data(&self, round_id: u128) -> Result<Round, Error> {
+        let round_data = self.contract.method::<_, RoundData>("getRoundData", round_id)?;
+        match call(round_data, self.identifier, self.call_timeout).await {
+            Ok(round_data) => return self.to_round(round_data),
+            Err(Error::Timeout) => return Err(Error::Timeout),
+            Err(_) => {}
+        }
+
+        let answer = self.contract.method("getAnswer", U256::from(round_id))?;
+        let answer: I256 = call(answer, self.identifier, self.call_timeout).await?;
+        let updated_at = self.contract.method("getTimestamp", U256::from(round_id))?;
+        let updated_at: U256 = call(updated_at, self.identifier, self.call_timeout).await?;
+
+        self.to_round((round_id, answer, updated_at, updated_at, round_id))
+    }
+
+    /// Converts the raw round data of this aggregator into a `Round`.
+    fn to_round(&self, round_data: RoundData) -> Result<Round, Error> {
+        let (round_id, answer, started_at, updated_at, answered_in_round) = round_data;
 
         // Convert the contract answer into a human-readable answer
-        let human_answer = float_answer / (10f64.powi(self.decimals.into()));
+        let human_answer = scale(answer, self.decimals)?;
 
         Ok(Round {
             identifier: self.identifier.to_string(),
+            chain_id: self.chain_id,
             round_id,
             answered_in_round,
             started_at,
             updated_at,
             answer: human_answer,
+            raw_answer: answer,
+            decimals: self.decimals,
+            smoothed_answer: None,
+            velocity: None,
+            previous_answer: None,
+            previous_round_id: None,
+            quote: None,
+            description: self.description.clone(),
+            exact_answer: None,
+            seq: 0,
         })
     }
 }
@@ -85,23 +642,400 @@ impl<'a> ChainlinkContract<'a> {
 #[cfg(test)]
 mod tests {
 
-    use ethers::{abi::Address, providers::Provider};
-    use crate::interface::ChainlinkContract;
+    use std::time::Duration;
+
+    use ethers::{
+        abi::{encode, Address, Token},
+        providers::{JsonRpcError, Middleware, MockProvider, MockResponse, Provider},
+        types::{Bytes, I256, U256},
+    };
+    use crate::error::Error;
+    use crate::interface::{quote_of, ChainlinkContract, Round};
+
+    fn round(raw_answer: &str, decimals: u8) -> Round {
+        let raw_answer = I256::from_dec_str(raw_answer).unwrap();
+        Round {
+            identifier: "STETH/ETH".to_string(),
+            chain_id: 1,
+            round_id: 1,
+            answered_in_round: 1,
+            answer: raw_answer.to_string().parse::<f64>().unwrap() / 10f64.powi(decimals.into()),
+            raw_answer,
+            decimals,
+            ..Default::default()
+        }
+    }
+
+    /// Queues the responses to `ChainlinkContract::new`, popped before those queued earlier.
+    fn mock_contract(mock: &MockProvider, decimals: u8) {
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::String("ETH / USD".into())]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(decimals.into())]))).unwrap();
+    }
+
+    /// Connects to the ETH / USD feed on BNB Smart Chain through `provider`.
+    async fn eth_contract<M: Middleware + Clone>(provider: &M) -> Result<ChainlinkContract<'_, M>, Error> {
+        let address = "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e".parse::<Address>().unwrap();
+        ChainlinkContract::new(provider, "ETH", address, 56).await
+    }
 
     #[tokio::test]
     async fn valid_answer() {
 
         let provider=Provider::try_from("https://bsc-dataseed1.binance.org/").unwrap();
 
-        let chainlink_contract = ChainlinkContract::new(
-            &provider,
-            "ETH",
-            "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e".parse::<Address>().unwrap(),
-        )
-        .await
-        .unwrap();
+        let chainlink_contract = eth_contract(&provider).await.unwrap();
         let price_data = chainlink_contract.latest_round_data().await.unwrap();
         println!("Received data: {:#?}", price_data);
         assert!(price_data.answer.ge(&0f64));
     }
+
+    #[test]
+    fn exact_answer_for_18_decimal_feeds() {
+        let steth = round("999712345678901234567", 18);
+        assert_eq!(steth.answer_decimal(), "999.712345678901234567");
+        assert!((steth.answer - 999.712_345_678_901_2).abs() < 1e-12);
+
+        assert_eq!(round("1500000000000000000", 18).answer_decimal(), "1.5");
+        assert_eq!(round("42", 18).answer_decimal(), "0.000000000000000042");
+        assert_eq!(round("-42", 18).answer_decimal(), "-0.000000000000000042");
+        assert_eq!(round("0", 18).answer_decimal(), "0");
+    }
+
+    #[test]
+    fn exact_answer_for_8_decimal_feeds() {
+        assert_eq!(round("243155000000", 8).answer_decimal(), "2431.55");
+        assert_eq!(round("243155000000", 0).answer_decimal(), "243155000000");
+    }
+
+    #[test]
+    fn rounded_answer() {
+        assert_eq!(round("243155500000", 8).answer_rounded(2), 2431.56);
+        assert_eq!(round("243155499999", 8).answer_rounded(2), 2431.55);
+        assert_eq!(round("-243155500000", 8).answer_rounded(2), -2431.56);
+        assert_eq!(round("243155500000", 8).answer_rounded(0), 2432.0);
+        assert_eq!(round("243155500001", 8).answer_rounded(12), 2431.55500001);
+        assert_eq!(round("999712345678901234567", 18).answer_rounded(4), 999.7123);
+        assert_eq!(round("243155500000", 90).answer_rounded(2), 0.0);
+        let min = round(&I256::MIN.to_string(), 18).answer_rounded(0);
+        assert!((min / -2f64.powi(255) * 1e18 - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn spread_in_basis_points() {
+        let peg = round("100000000", 8);
+        assert_eq!(round("99500000", 8).spread_bps(&peg), Some(-50));
+        assert_eq!(round("1012345", 6).spread_bps(&peg), Some(123));
+        assert_eq!(round("1000049999999999999", 18).spread_bps(&peg), Some(0));
+        assert_eq!(round("1000050000000000000", 18).spread_bps(&peg), Some(1));
+        assert_eq!(round("999950000000000000", 18).spread_bps(&peg), Some(-1));
+        assert_eq!(round("0", 8).spread_bps(&peg), None);
+        assert_eq!(round("1", 0).spread_bps(&round("1", 80)), None);
+        assert_eq!(round("1", 0).spread_bps(&round("1", 77)), None);
+    }
+
+    #[test]
+    fn quote_from_description() {
+        assert_eq!(quote_of("ETH / USD").as_deref(), Some("USD"));
+        assert_eq!(quote_of("STETH/ETH").as_deref(), Some("ETH"));
+        assert_eq!(quote_of("Total Value Locked"), None);
+        assert_eq!(quote_of("ETH / "), None);
+    }
+
+    #[test]
+    fn change_in_basis_points() {
+        let previous = round("100000000", 8);
+        assert!(round("100500000", 8).changed_from(&previous, 50));
+        assert!(round("99500000", 8).changed_from(&previous, 50));
+        assert!(!round("100499999", 8).changed_from(&previous, 50));
+        assert!(round("1005000000000000000", 18).changed_from(&previous, 50));
+        assert!(!round("100000000", 8).changed_from(&previous, 0));
+        assert!(round("1", 8).changed_from(&round("0", 8), 10_000));
+        assert!(round("1", 0).changed_from(&round("1", 80), 50));
+        assert!(round("1", 0).changed_from(&round("1", 77), 50));
+    }
+
+    #[test]
+    fn answer_in_cents() {
+        assert_eq!(round("243155500000", 8).answer_usd_cents(), Some(243156));
+        assert_eq!(round("243155499999", 8).answer_usd_cents(), Some(243155));
+        assert_eq!(round("7", 0).answer_usd_cents(), Some(700));
+        assert_eq!(round("-1", 8).answer_usd_cents(), None);
+        assert_eq!(round("999999999999999999999", 0).answer_usd_cents(), None);
+        assert_eq!(round("243155500000", 80).answer_usd_cents(), Some(0));
+
+        let mut steth = round("999712345678901234567", 18);
+        steth.quote = Some("ETH".to_string());
+        assert_eq!(steth.answer_usd_cents(), None);
+        steth.quote = Some("USD".to_string());
+        assert_eq!(steth.answer_usd_cents(), Some(99971));
+    }
+
+    #[test]
+    fn fixed_digits_answer() {
+        assert_eq!(round("243155000000", 8).answer_string(), "2431.55000000");
+        assert_eq!(round("-42", 8).answer_string(), "-0.00000042");
+        assert_eq!(round("42", 0).answer_string(), "42");
+    }
+
+    #[test]
+    fn round_id_encoding() {
+        let round_id = (2u128 << 64) | 10_685;
+        assert_eq!(Round::phase_id(round_id), 2);
+        assert_eq!(Round::aggregator_round_id(round_id), 10_685);
+        assert_eq!(Round::phase_id(42), 0);
+    }
+
+    #[test]
+    fn csv_record() {
+        let mut round = round("243155000000", 8);
+        round.updated_at = U256::from(1716000000u64);
+        assert_eq!(
+            Round::csv_header(),
+            "identifier,chain_id,round_id,answer,raw_answer,decimals,updated_at"
+        );
+        assert_eq!(round.to_csv_record(), "STETH/ETH,1,1,2431.55,243155000000,8,1716000000");
+
+        round.identifier = "ETH, \"USD\"".to_string();
+        assert!(round.to_csv_record().starts_with("\"ETH, \"\"USD\"\"\",1,"));
+    }
+
+    #[test]
+    fn raw_answer_is_serialized_as_a_decimal_string() {
+        let round = round("-999712345678901234567", 18);
+        let json = serde_json::to_value(&round).unwrap();
+        assert_eq!(json["raw_answer"], "-999712345678901234567");
+        assert_eq!(serde_json::from_value::<Round>(json).unwrap().raw_answer, round.raw_answer);
+
+        // Hex strings, as I256 serializes itself, are still read
+        let mut json = serde_json::to_value(&round).unwrap();
+        json["raw_answer"] = serde_json::to_value(round.raw_answer).unwrap();
+        assert_eq!(serde_json::from_value::<Round>(json).unwrap().raw_answer, round.raw_answer);
+    }
+
+    #[tokio::test]
+    async fn answer_of_legacy_aggregators() {
+        let (provider, mock) = Provider::mocked();
+        // Queued last in first out: decimals, a reverting getRoundData, getAnswer and getTimestamp
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(1_700_000_000u64.into())]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Int(U256::from(243_155_000_000u64))]))).unwrap();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+        mock_contract(&mock, 8);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        assert_eq!(contract.answer_at(U256::from(42)).await.unwrap(), 2431.55);
+    }
+
+    #[tokio::test]
+    async fn offchain_lookups_are_recognized() {
+        let (provider, mock) = Provider::mocked();
+        let mut revert = vec![0x55, 0x6f, 0x18, 0x30];
+        revert.extend(encode(&[
+            Token::Address(Address::zero()),
+            Token::Array(vec![Token::String("https://gateway.example/{data}".to_string())]),
+            Token::Bytes(Vec::new()),
+            Token::FixedBytes(vec![0; 4]),
+            Token::Bytes(Vec::new()),
+        ]));
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(Bytes::from(revert).to_string().into()),
+        }));
+
+        let result = eth_contract(&provider).await;
+        assert!(matches!(result, Err(Error::OffchainLookup(urls)) if urls == ["https://gateway.example/{data}"]));
+    }
+
+    fn round_data(round_id: u64) -> Bytes {
+        Bytes::from(encode(&[
+            Token::Uint(round_id.into()),
+            Token::Int(U256::from(243_155_000_000u64)),
+            Token::Uint(1_700_000_000u64.into()),
+            Token::Uint(1_700_000_000u64.into()),
+            Token::Uint(round_id.into()),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn recent_rounds_skip_missing_rounds() {
+        let (provider, mock) = Provider::mocked();
+        let revert = || {
+            MockResponse::Error(JsonRpcError {
+                code: 3,
+                message: "execution reverted".to_string(),
+                data: None,
+            })
+        };
+        // Queued last in first out: decimals, latestRoundData, round 9, round 8 and its legacy fallback
+        mock.push_response(revert());
+        mock.push_response(revert());
+        mock.push::<Bytes, _>(round_data(9)).unwrap();
+        mock.push::<Bytes, _>(round_data(10)).unwrap();
+        mock_contract(&mock, 8);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        let rounds = contract.recent_rounds(2).await.unwrap();
+        let round_ids: Vec<u128> = rounds.iter().map(|round| round.round_id).collect();
+        assert_eq!(round_ids, [10, 9]);
+    }
+
+    #[tokio::test]
+    async fn suggested_interval_is_the_median_update_delta() {
+        let (provider, mock) = Provider::mocked();
+        let round_data = |round_id: u64, updated_at: U256| {
+            Bytes::from(encode(&[
+                Token::Uint(round_id.into()),
+                Token::Int(U256::from(243_155_000_000u64)),
+                Token::Uint(updated_at),
+                Token::Uint(updated_at),
+                Token::Uint(round_id.into()),
+            ]))
+        };
+        // A garbage timestamp far beyond u64 gives no usable delta
+        mock.push::<Bytes, _>(round_data(1, U256::from(1_700_000_000u64))).unwrap();
+        mock.push::<Bytes, _>(round_data(2, U256::MAX)).unwrap();
+        // The first round of an aggregator has nothing before it to measure against
+        mock.push::<Bytes, _>(round_data(1, U256::from(1_700_000_000u64))).unwrap();
+        // Queued last in first out: rounds 4 to 1, walking back from the latest
+        mock.push::<Bytes, _>(round_data(1, U256::from(1_700_000_000u64))).unwrap();
+        mock.push::<Bytes, _>(round_data(2, U256::from(1_700_001_800u64))).unwrap();
+        mock.push::<Bytes, _>(round_data(3, U256::from(1_700_005_400u64))).unwrap();
+        mock.push::<Bytes, _>(round_data(4, U256::from(1_700_009_000u64))).unwrap();
+        mock_contract(&mock, 8);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        assert_eq!(contract.suggested_interval().await, Some(Duration::from_secs(3600)));
+        assert_eq!(contract.suggested_interval().await, None);
+        assert_eq!(contract.suggested_interval().await, None);
+    }
+
+    #[tokio::test]
+    async fn timestamp_of_v3_only_aggregators() {
+        let (provider, mock) = Provider::mocked();
+        // Queued last in first out: decimals, a reverting getTimestamp and getRoundData
+        mock.push::<Bytes, _>(round_data(9)).unwrap();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+        mock_contract(&mock, 8);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        assert_eq!(contract.round_timestamp(9).await.unwrap(), U256::from(1_700_000_000u64));
+    }
+
+    #[tokio::test]
+    async fn raw_latest_round() {
+        let (provider, mock) = Provider::mocked();
+        let answer = I256::from_dec_str("-999712345678901234567").unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[
+            Token::Uint(7.into()),
+            Token::Int(answer.into_raw()),
+            Token::Uint(1_700_000_000u64.into()),
+            Token::Uint(1_700_000_060u64.into()),
+            Token::Uint(7.into()),
+        ])))
+        .unwrap();
+        mock_contract(&mock, 18);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        let raw = contract.latest_round_raw().await.unwrap();
+        assert_eq!(raw, (7, answer, U256::from(1_700_000_000u64), U256::from(1_700_000_060u64), 7));
+    }
+
+    #[tokio::test]
+    async fn reverts_name_the_feed() {
+        let (provider, mock) = Provider::mocked();
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+
+        let result = eth_contract(&provider).await;
+        assert!(matches!(result, Err(Error::ContractReverted { identifier }) if identifier == "ETH"));
+    }
+
+    #[tokio::test]
+    async fn rate_limits_are_recognized() {
+        let (provider, mock) = Provider::mocked();
+        let error = |code: i64, message: &str| {
+            MockResponse::Error(JsonRpcError {
+                code,
+                message: message.to_string(),
+                data: None,
+            })
+        };
+        mock.push_response(error(-32000, "header not found"));
+        mock.push_response(error(-32000, "Rate limit reached, retry later"));
+        mock.push_response(error(429, "Too Many Requests"));
+
+        assert!(matches!(eth_contract(&provider).await, Err(Error::RateLimited(_))));
+        assert!(matches!(eth_contract(&provider).await, Err(Error::RateLimited(_))));
+        assert!(matches!(eth_contract(&provider).await, Err(Error::Call(_))));
+    }
+
+    #[tokio::test]
+    async fn addresses_without_an_aggregator_are_an_error() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::new()).unwrap();
+
+        let address = "0x000000000000000000000000000000000000dEaD".parse::<Address>().unwrap();
+        let result = ChainlinkContract::new(&provider, "ETH", address, 56).await;
+        assert!(matches!(result, Err(Error::Call(_))));
+
+        mock.push::<Bytes, _>(Bytes::from(vec![0u8; 31])).unwrap();
+        mock_contract(&mock, 8);
+        let contract = ChainlinkContract::new(&provider, "ETH", address, 56).await.unwrap();
+        assert!(matches!(contract.latest_round_data().await, Err(Error::Call(_))));
+    }
+
+    #[tokio::test]
+    async fn historical_rounds() {
+        let (provider, mock) = Provider::mocked();
+        let round_data = |round_id: u64, answer: i64, updated_at: u64| {
+            Bytes::from(encode(&[
+                Token::Uint(round_id.into()),
+                Token::Int(I256::from(answer).into_raw()),
+                Token::Uint(updated_at.into()),
+                Token::Uint(updated_at.into()),
+                Token::Uint(round_id.into()),
+            ]))
+        };
+        // Newer aggregators answer rounds they do not know with zeros
+        mock.push::<Bytes, _>(round_data(0, 0, 0)).unwrap();
+        mock.push::<Bytes, _>(round_data(5, 243_155_000_000, 1_700_000_000)).unwrap();
+        mock_contract(&mock, 8);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        let round = contract.historical_round_data(5).await.unwrap();
+        assert_eq!((round.round_id, round.answer), (5, 2431.55));
+        assert!(matches!(contract.historical_round_data(1).await, Err(Error::RoundNotFound(1))));
+        assert!(matches!(contract.historical_round_data(2 << 64).await, Err(Error::RoundNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn latest_answer() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Int(I256::from(243_155_000_000i64).into_raw())]))).unwrap();
+        mock_contract(&mock, 8);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        assert_eq!(contract.latest_answer().await.unwrap(), 2431.55);
+    }
+
+    #[tokio::test]
+    async fn latest_timestamp() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(1_700_000_060u64.into())]))).unwrap();
+        mock_contract(&mock, 8);
+
+        let contract = eth_contract(&provider).await.unwrap();
+        assert_eq!(contract.latest_timestamp().await.unwrap(), U256::from(1_700_000_060u64));
+    }
 }
@@ -2,17 +2,69 @@
 /// This library provides a simple interface to fetch price data from the Chainlink decentralized data feed.
 /// Core is the main module that contains the main struct `Rustlink` that you will need to interact with.
 pub mod core;
-mod interface;
-mod error;
+#[doc(hidden)]
+pub mod config;
+pub mod interface;
+mod builder;
+pub mod clock;
+pub mod error;
 mod fetcher;
+pub mod transport;
+#[cfg(feature = "env-filter")]
+pub mod logging;
+#[cfg(feature = "proto")]
+pub mod proto;
 #[cfg(test)]
 mod tests {
 
-    use async_std::channel::unbounded;
+    use std::{sync::Arc, time::Duration};
 
-    use crate::core::{Reflector, Rustlink};
+    use async_std::channel::{unbounded, Sender};
+    use ethers::{
+        abi::{encode, Token},
+        providers::{JsonRpcError, MockProvider, MockResponse, Provider},
+        types::{Bytes, I256, U256},
+    };
+    use futures::StreamExt;
+
+    use crate::core::{merge_streams, parse_feed_spec, weighted_index, ErrorBudget, FetchEvent, Reflector, Round, RoundSink, Rustlink, RustlinkBuilder};
+    use crate::clock::MockClock;
+    use crate::error::Error;
+
+    /// Queues the response to one fetch of a round of an 8 decimals feed. The mock pops its
+    /// responses last in first out, so queue the latest fetch first.
+    fn mock_round(mock: &MockProvider, round_id: u128, answer: i64) {
+        mock_stale_round(mock, round_id, round_id, answer);
+    }
+
+    /// Queues the responses to connecting to the contract of an `ETH / USD` feed with 8 decimals,
+    /// which happens once before its first round is fetched. Queue it after that round.
+    fn mock_contract(mock: &MockProvider) {
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::String("ETH / USD".into())]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(8.into())]))).unwrap();
+    }
+
+    /// Like `mock_round`, for a round answered in another round.
+    fn mock_stale_round(mock: &MockProvider, round_id: u128, answered_in_round: u128, answer: i64) {
+        let round_data = encode(&[
+            Token::Uint(round_id.into()),
+            Token::Int(I256::from(answer).into_raw()),
+            Token::Uint(1_700_000_000u64.into()),
+            Token::Uint(1_700_000_000u64.into()),
+            Token::Uint(answered_in_round.into()),
+        ]);
+        mock.push::<Bytes, _>(Bytes::from(round_data)).unwrap();
+    }
+
+    /// A builder fetching the ETH / USD feed on BNB Smart Chain.
+    fn eth_feed() -> RustlinkBuilder {
+        RustlinkBuilder::default()
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .with_chain_id(56)
+    }
 
     #[tokio::test]
+    #[allow(clippy::vec_init_then_push)]
     async fn ensure_price_is_received() {
         let mut contracts: Vec<(String, String)> = Vec::new();
         contracts.push((
@@ -23,16 +75,961 @@ mod tests {
         let (sender, receiver) = unbounded();
 
         let rustlink = Rustlink::try_new(
-            "https://bsc-dataseed1.binance.org/",
+            vec!["https://bsc-dataseed1.binance.org/".to_string()],
             1,
             Reflector::Sender(sender),
             contracts,
         )
         .unwrap();
 
-        rustlink.start();
+        rustlink.start().unwrap();
         let round_data = receiver.recv().await.unwrap();
         println!("Received data: {:#?}", round_data);
         assert!(round_data.answer.ge(&0f64));
     }
+
+    #[tokio::test]
+    async fn tick_is_emitted_when_every_fetch_fails() {
+        let (sender, receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .with_fetch_interval_seconds(1)
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .with_tick_events(true)
+            .build("http://127.0.0.1:1", Reflector::Events(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::Tick { at } if at > 0));
+        rustlink.stop().await.unwrap();
+
+        let status = rustlink.feed_status();
+        assert_eq!(status[0].identifier, "ETH");
+        assert_eq!(status[0].consecutive_failures, 1);
+        assert!(status[0].last_error.is_some() && status[0].last_success.is_none());
+    }
+
+    #[tokio::test]
+    async fn ipc_connection_failure_is_reported() {
+        let (sender, _receiver) = unbounded();
+
+        let result = Rustlink::try_new_ipc(
+            "/nonexistent/rustlink.ipc",
+            1,
+            Reflector::Sender(sender),
+            Vec::new(),
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn fetches_through_any_middleware() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        // Stopping before starting does nothing
+        rustlink.stop().await.unwrap();
+        rustlink.start().unwrap();
+        // Neither does starting twice
+        rustlink.start().unwrap();
+        assert!(rustlink.is_running());
+        let round = receiver.recv().await.unwrap();
+        assert_eq!((round.chain_id, round.round_id), (56, 7));
+        assert_eq!(round.answer_decimal(), "2431.55");
+        rustlink.stop().await.unwrap();
+        rustlink.stop().await.unwrap();
+        assert!(!rustlink.is_running());
+        // The channel was closed by the stop, so there is nothing to restart for
+        assert!(matches!(rustlink.start(), Err(Error::ReflectorClosed)));
+        assert!(!rustlink.is_running());
+    }
+
+    #[tokio::test]
+    async fn failed_fetches_are_retried() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        for _ in 0..2 {
+            mock.push_response(MockResponse::Error(JsonRpcError {
+                code: 429,
+                message: "rate limited".to_string(),
+                data: None,
+            }));
+        }
+        mock_contract(&mock);
+        // Connecting to the contract is retried as well
+        mock.push_response(MockResponse::Error(JsonRpcError {
+            code: -32005,
+            message: "limit exceeded".to_string(),
+            data: None,
+        }));
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(60)
+            .with_max_retries(2)
+            .with_retry_base_delay_ms(10)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        // The round arrives within the first pass rather than a minute later
+        let round = async_std::future::timeout(Duration::from_secs(5), receiver.recv()).await;
+        assert_eq!(round.unwrap().unwrap().round_id, 7);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn unreachable_providers_are_failed_over() {
+        // The primary mock has no responses, as if the endpoint were down
+        let (provider, _) = Provider::mocked();
+        let (fallback, mock) = Provider::mocked();
+        mock_round(&mock, 8, 243_255_000_000);
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let mut rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_emit_on_start(true)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+        rustlink.configuration.fallback_providers.push(fallback);
+
+        rustlink.start().unwrap();
+        assert_eq!(receiver.recv().await.unwrap().round_id, 7);
+        // The fallback stays active rather than the primary being tried again
+        assert_eq!(receiver.recv().await.unwrap().round_id, 8);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[test]
+    fn active_rpc_follows_failovers() {
+        let (sender, _receiver) = unbounded();
+        let rustlink = Rustlink::try_new(
+            vec!["http://127.0.0.1:1/".to_string(), "http://127.0.0.1:2/".to_string()],
+            1,
+            Reflector::Sender(sender),
+            Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(rustlink.active_rpc(), "http://127.0.0.1:1/");
+        rustlink.fail_over();
+        assert_eq!(rustlink.active_rpc(), "http://127.0.0.1:2/");
+        rustlink.fail_over();
+        assert_eq!(rustlink.active_rpc(), "http://127.0.0.1:1/");
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_rounds() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_max_rounds(1)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        receiver.recv().await.unwrap();
+        // The loop completes the shutdown handshake without being stopped
+        rustlink.shutdown_recv.recv().await.unwrap();
+        // The channel is closed rather than left waiting for rounds that never come
+        assert!(receiver.recv().await.is_err());
+        // Stopping afterwards, even twice, is harmless
+        rustlink.stop().await.unwrap();
+        rustlink.stop().await.unwrap();
+        assert!(!rustlink.is_running());
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_runtime() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(3600)
+            .with_emit_on_start(true)
+            .with_max_runtime(Duration::from_millis(200))
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        receiver.recv().await.unwrap();
+        // The loop gives up waiting for the next tick once the runtime is over
+        rustlink.shutdown_recv.recv().await.unwrap();
+        assert!(receiver.recv().await.is_err());
+        assert!(!rustlink.is_running());
+    }
+
+    #[tokio::test]
+    async fn rounds_can_be_printed() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+
+        let rustlink = eth_feed()
+            .with_emit_on_start(true)
+            .with_max_rounds(1)
+            .build_with_provider(provider, Reflector::Stdout)
+            .unwrap();
+
+        rustlink.start().unwrap();
+        rustlink.shutdown_recv.recv().await.unwrap();
+        assert!(!rustlink.is_running());
+    }
+
+    #[test]
+    fn invalid_configuration_is_reported() {
+        let (sender, _receiver) = unbounded();
+
+        let result = Rustlink::try_new(vec!["not a url".to_string()], 1, Reflector::Sender(sender.clone()), Vec::new());
+        assert!(matches!(result, Err(Error::InvalidRpcUrl(_))));
+        let result = Rustlink::try_new(Vec::new(), 1, Reflector::Sender(sender.clone()), Vec::new());
+        assert!(matches!(result, Err(Error::InvalidRpcUrl(_))));
+
+        let result = Rustlink::try_new(
+            vec!["https://bsc-dataseed1.binance.org/".to_string()],
+            1,
+            Reflector::Sender(sender.clone()),
+            vec![("ETH".to_string(), "0x9ef1".to_string())],
+        );
+        assert!(matches!(result, Err(Error::InvalidAddress(_))));
+
+        // A typo in the checksummed address (d instead of D) is only caught when strict
+        let builder = RustlinkBuilder::default().with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883dC6401d5b2e");
+        let url = "https://bsc-dataseed1.binance.org/";
+        assert!(builder.clone().build(url, Reflector::Sender(sender.clone())).is_ok());
+        let result = builder.with_strict_checksum(true).build(url, Reflector::Sender(sender.clone()));
+        assert!(matches!(result, Err(Error::InvalidAddress(_))));
+        let builder = RustlinkBuilder::default()
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .with_strict_checksum(true);
+        assert!(builder.build(url, Reflector::Sender(sender)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn streams_are_merged() {
+        let (ethereum, ethereum_rounds) = unbounded();
+        let (arbitrum, arbitrum_rounds) = unbounded();
+        for (sender, chain_id) in [(&ethereum, 1), (&arbitrum, 42_161), (&ethereum, 1)] {
+            sender.send(Round { chain_id, ..Default::default() }).await.unwrap();
+        }
+        drop((ethereum, arbitrum));
+
+        let rounds: Vec<Round> = merge_streams(vec![ethereum_rounds, arbitrum_rounds]).collect().await;
+        let mut chain_ids: Vec<u64> = rounds.iter().map(|round| round.chain_id).collect();
+        chain_ids.sort_unstable();
+        assert_eq!(chain_ids, [1, 1, 42_161]);
+    }
+
+    #[test]
+    fn index_is_weighted_over_its_components() {
+        let component = |raw_answer: i64, round_id: u128, answered_in_round: u128| Round {
+            round_id,
+            answered_in_round,
+            raw_answer: I256::from(raw_answer),
+            decimals: 8,
+            ..Default::default()
+        };
+        let eth = component(243_155_000_000, 7, 7);
+        let btc = component(6_500_000_000_000, 3, 3);
+
+        assert_eq!(weighted_index(&[(eth.clone(), 0.5), (btc.clone(), 0.1)]), Some(1215.775 + 6500.0));
+        assert_eq!(weighted_index(&[(eth, 0.5), (component(6_500_000_000_000, 3, 2), 0.1)]), None);
+        assert_eq!(weighted_index(&[]), None);
+    }
+
+    #[test]
+    fn feed_specs_are_parsed() {
+        let (chain_id, identifier, address) =
+            parse_feed_spec("1:ETH:0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419").unwrap();
+        assert_eq!((chain_id, identifier.as_str()), (1, "ETH"));
+        assert_eq!(address, "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419".parse().unwrap());
+
+        assert!(matches!(parse_feed_spec("ETH:0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"), Err(Error::InvalidFeedSpec(_))));
+        assert!(matches!(parse_feed_spec("one:ETH:0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"), Err(Error::InvalidFeedSpec(_))));
+        assert!(matches!(parse_feed_spec("1::0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"), Err(Error::InvalidFeedSpec(_))));
+        assert!(matches!(parse_feed_spec("1:ETH:0x5f4e"), Err(Error::InvalidAddress(_))));
+    }
+
+    #[tokio::test]
+    async fn all_decimals_are_queried() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(18.into())]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(18.into())]))).unwrap();
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .with_contract("1INCH", "0x9a177Bb9f5b6083E962f9e62bD21d4b5660Aeb03")
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        let decimals = rustlink.all_decimals().await.unwrap();
+        assert_eq!(decimals.len(), 2);
+        assert_eq!((decimals[&(0, "ETH".to_string())], decimals[&(0, "1INCH".to_string())]), (18, 18));
+
+        // The mock has no responses left
+        assert!(matches!(rustlink.all_decimals().await, Err(Error::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn feeds_are_listed_with_their_metadata() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::String("ETH / USD".into())]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(8.into())]))).unwrap();
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        let feeds = rustlink.feeds().await.unwrap();
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds[0].identifier, "ETH");
+        assert_eq!(feeds[0].address, "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e".parse().unwrap());
+        assert_eq!(feeds[0].decimals, 8);
+        assert_eq!(feeds[0].description.as_deref(), Some("ETH / USD"));
+    }
+
+    #[tokio::test]
+    async fn overridden_decimals_are_not_queried() {
+        let (provider, mock) = Provider::mocked();
+        let round_data = encode(&[
+            Token::Uint(7.into()),
+            Token::Int(I256::from(2_431_550_000_000_000_000_000i128).into_raw()),
+            Token::Uint(1_700_000_000u64.into()),
+            Token::Uint(1_700_000_000u64.into()),
+            Token::Uint(7.into()),
+        ]);
+        // Only the round and the description are queued, querying decimals would fail the fetch
+        mock.push::<Bytes, _>(Bytes::from(round_data)).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::String("ETH / USD".into())]))).unwrap();
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_decimals_override("ETH", 18)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        let round = rustlink.get_latest("ETH").await.unwrap();
+        assert_eq!((round.decimals, round.answer_decimal()), (18, "2431.55".to_string()));
+        assert_eq!(rustlink.all_decimals().await.unwrap()[&(0, "ETH".to_string())], 18);
+    }
+
+    #[tokio::test]
+    async fn emits_on_start_without_waiting_for_the_interval() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(3600)
+            .with_emit_on_start(true)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        let round = async_std::future::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(round.round_id, 7);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn subscribes_to_a_single_feed() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 8, 100_000_000);
+        mock_contract(&mock);
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .with_fetch_interval_seconds(1)
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .with_contract("USDT", "0xB97Ad0E74fa7d920791E90258A6E2085088b4320")
+            .with_chain_id(56)
+            .with_emit_on_start(true)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+        assert!(matches!(rustlink.subscribe("BTC"), Err(Error::NotFound)));
+        let usdt = rustlink.subscribe("USDT").unwrap();
+
+        rustlink.start().unwrap();
+        assert_eq!(usdt.recv().await.unwrap().round_id, 8);
+        assert_eq!(receiver.recv().await.unwrap().identifier, "ETH");
+        assert_eq!(receiver.recv().await.unwrap().identifier, "USDT");
+        assert!(usdt.is_empty());
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rounds_are_batched_within_the_window() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 8, 100_000_000);
+        mock_contract(&mock);
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(3600)
+            .with_contract("USDT", "0xB97Ad0E74fa7d920791E90258A6E2085088b4320")
+            .with_emit_on_start(true)
+            .with_batch_window(Duration::from_millis(200))
+            .build_with_provider(provider, Reflector::BatchSender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        // Emitted once the window is over, not an interval later with the next fetch
+        let rounds = async_std::future::timeout(Duration::from_secs(5), receiver.recv()).await;
+        let round_ids: Vec<u128> = rounds.unwrap().unwrap().iter().map(|round| round.round_id).collect();
+        assert_eq!(round_ids, [7, 8]);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn changed_decimals_are_noticed() {
+        let (provider, mock) = Provider::mocked();
+        // Reconnecting after the aggregator was upgraded to 18 decimals
+        mock_round(&mock, 8, 2_431_550_000_000_000_000);
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::String("ETH / USD".into())]))).unwrap();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(18.into())]))).unwrap();
+        // The check of the second pass
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(18.into())]))).unwrap();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_emit_on_start(true)
+            .with_decimals_check_ticks(1)
+            .build_with_provider(provider, Reflector::Events(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        assert!(matches!(receiver.recv().await.unwrap(), FetchEvent::Round(round) if round.answer == 2431.55));
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::DecimalsChanged { identifier, old: 8, new: 18 } if identifier == "ETH"));
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::Round(round) if round.decimals == 18 && round.answer == 2.43155));
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rounds_of_every_chain_reach_the_reflector() {
+        let (bsc, bsc_mock) = Provider::mocked();
+        mock_round(&bsc_mock, 7, 243_155_000_000);
+        mock_contract(&bsc_mock);
+        let (arbitrum, arbitrum_mock) = Provider::mocked();
+        mock_round(&arbitrum_mock, 9, 243_160_000_000);
+        mock_contract(&arbitrum_mock);
+        let (sender, receiver) = unbounded();
+
+        let mut rustlink = eth_feed()
+            .with_emit_on_start(true)
+            .with_sanity_range("ETH", 100.0, 100_000.0)
+            .build_with_provider(bsc, Reflector::Sender(sender))
+            .unwrap();
+        // The same identifier on another chain is a feed of its own
+        let eth = vec![("ETH".to_string(), "0x639Fe6ab55C921f74e7fac1ee960C0B6293ba612".to_string())];
+        let arbitrum_index = rustlink.add_chain_with_provider(arbitrum, Some(42161), eth).unwrap();
+        let on_arbitrum = rustlink.subscribe_on(arbitrum_index, "ETH").unwrap();
+
+        rustlink.start().unwrap();
+        let first = receiver.recv().await.unwrap();
+        let second = receiver.recv().await.unwrap();
+        assert_eq!((first.chain_id, first.round_id), (56, 7));
+        assert_eq!((second.chain_id, second.round_id), (42161, 9));
+        assert_eq!(on_arbitrum.recv().await.unwrap().chain_id, 42161);
+        assert!(on_arbitrum.is_empty());
+        rustlink.stop().await.unwrap();
+
+        let status: Vec<_> = rustlink.feed_status().into_iter().map(|status| (status.chain_index, status.consecutive_failures)).collect();
+        assert_eq!(status, [(0, 0), (1, 0)]);
+        assert_eq!(rustlink.describe()["sanity_ranges"]["0"]["ETH"][1], 100_000.0);
+    }
+
+    #[tokio::test]
+    async fn chains_sharing_a_chain_id_keep_their_own_state() {
+        let (primary, primary_mock) = Provider::mocked();
+        mock_round(&primary_mock, 7, 243_155_000_000);
+        mock_contract(&primary_mock);
+        let (secondary, secondary_mock) = Provider::mocked();
+        mock_round(&secondary_mock, 7, 243_160_000_000);
+        mock_contract(&secondary_mock);
+        let (sender, receiver) = unbounded();
+
+        let mut rustlink = eth_feed()
+            .with_emit_on_start(true)
+            .with_dedup(true)
+            .build_with_provider(primary, Reflector::Sender(sender))
+            .unwrap();
+        // A second provider of the same network, answering the same round id
+        let eth = vec![("ETH".to_string(), "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e".to_string())];
+        rustlink.add_chain_with_provider(secondary, Some(56), eth).unwrap();
+
+        rustlink.start().unwrap();
+        let first = receiver.recv().await.unwrap();
+        let second = receiver.recv().await.unwrap();
+        rustlink.stop().await.unwrap();
+        assert_eq!((first.chain_id, first.round_id, first.answer), (56, 7, 2431.55));
+        assert_eq!((second.chain_id, second.round_id, second.answer), (56, 7, 2431.6));
+        assert_eq!((first.previous_answer, second.previous_answer), (None, None));
+        assert_eq!((first.velocity, second.velocity), (None, None));
+    }
+
+    #[tokio::test]
+    async fn sequence_numbers_increase_across_feeds() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 9, 243_155_000_000);
+        mock_round(&mock, 8, 100_000_000);
+        mock_contract(&mock);
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_contract("USDT", "0xB97Ad0E74fa7d920791E90258A6E2085088b4320")
+            .with_emit_on_start(true)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        let mut rounds = Vec::new();
+        for _ in 0..3 {
+            let round = receiver.recv().await.unwrap();
+            rounds.push((round.identifier, round.seq));
+        }
+        rustlink.stop().await.unwrap();
+        assert_eq!(rounds, [("ETH".to_string(), 0), ("USDT".to_string(), 1), ("ETH".to_string(), 2)]);
+    }
+
+    #[tokio::test]
+    async fn outliers_are_rejected() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 8, 243_155_000_000);
+        mock_round(&mock, 7, 1_000_000_000_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_sanity_range("ETH", 100.0, 100_000.0)
+            .build_with_provider(provider, Reflector::Events(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::OutlierRejected(round) if round.round_id == 7));
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::Round(round) if round.round_id == 8));
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn custom_sinks_receive_rounds() {
+        struct Collector(Sender<u128>);
+
+        #[async_trait::async_trait]
+        impl RoundSink for Collector {
+            async fn emit(&self, round: Round) {
+                self.0.send(round.round_id).await.unwrap();
+            }
+        }
+
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .build_with_provider(provider, Reflector::Custom(Arc::new(Collector(sender))))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        assert_eq!(receiver.recv().await.unwrap(), 7);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rounds_are_passed_to_callbacks() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .build_with_provider(provider, Reflector::Callback(Arc::new(move |round: Round| {
+                sender.try_send(round.round_id).unwrap();
+            })))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        assert_eq!(receiver.recv().await.unwrap(), 7);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rounds_are_broadcast_to_every_receiver() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, mut first) = tokio::sync::broadcast::channel(16);
+        let mut second = sender.subscribe();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .build_with_provider(provider, Reflector::Broadcast(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        let (first, second) = futures::join!(first.recv(), second.recv());
+        assert_eq!(first.unwrap().round_id, 7);
+        assert_eq!(second.unwrap().round_id, 7);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn ping_reports_rpc_health() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U256::from(56)).unwrap();
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        assert!(rustlink.ping().await.is_ok());
+        assert!(matches!(rustlink.ping().await, Err(Error::Connection(_))));
+    }
+
+    #[tokio::test]
+    async fn stale_data_is_refused_on_demand() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        // The mocked rounds were updated ten minutes before the clock
+        let clock = Arc::new(MockClock::new((1_700_000_000 + 600) * 1000));
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_clock(clock)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        let round = rustlink.get_latest_fresh("ETH", Duration::from_secs(3600)).await.unwrap();
+        assert_eq!(round.round_id, 7);
+        let result = rustlink.get_latest_fresh("ETH", Duration::from_secs(60)).await;
+        assert!(matches!(result, Err(Error::StaleData { age, .. }) if age == Duration::from_secs(600)));
+        assert!(matches!(rustlink.get_latest("BTC").await, Err(Error::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn snapshot_fetches_every_feed_once() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        let snapshot = rustlink.snapshot().await;
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].as_ref().unwrap().round_id, 7);
+        assert!(receiver.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stale_rounds_are_withheld() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 8, 243_155_000_000);
+        mock_stale_round(&mock, 7, 6, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .build_with_provider(provider, Reflector::Events(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(
+            event,
+            FetchEvent::StaleRound { round_id: 7, answered_in_round: 6, .. }
+        ));
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::Round(round) if round.round_id == 8));
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn user_agent_is_sent_to_the_rpc() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let request = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let read = std::io::Read::read(&mut stream, &mut request).unwrap();
+            String::from_utf8_lossy(&request[..read]).to_lowercase()
+        });
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .with_user_agent("my-service/1.0")
+            .with_request_ids(true)
+            .build(&url, Reflector::Sender(sender))
+            .unwrap();
+
+        // The listener hangs up without answering, only the request matters
+        let _ = rustlink.ping().await;
+        let request = request.join().unwrap();
+        assert!(request.contains("user-agent: my-service/1.0"));
+        assert!(request.contains("x-request-id: 1"));
+        assert!(request.contains("\"id\":1"));
+    }
+
+    #[tokio::test]
+    async fn snapshots_give_up_on_slow_feeds_at_the_deadline() {
+        // The listener never answers, so the fetch can only end with the deadline
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .build(&url, Reflector::Sender(sender))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let snapshot = rustlink.snapshot_with_deadline(Duration::from_millis(200)).await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(snapshot.len(), 1);
+        assert!(matches!(snapshot[0], Err(Error::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn calls_to_silent_endpoints_time_out() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, _receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_call_timeout_seconds(1)
+            .build(&url, Reflector::Sender(sender))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        assert!(matches!(rustlink.get_latest("ETH").await, Err(Error::Timeout)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn describes_the_configuration() {
+        let (sender, _receiver) = unbounded();
+        let rustlink = RustlinkBuilder::default()
+            .with_contract("ETH", "0x9ef1b8c0e4f7dc8bf5719ea496883dc6401d5b2e")
+            .with_chain_id(56)
+            .build("https://bsc-dataseed1.binance.org/?key=secret", Reflector::Sender(sender))
+            .unwrap();
+
+        let description = rustlink.describe();
+        assert_eq!(description["chain_id"], 56);
+        assert_eq!(description["contracts"][0]["address"], "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e");
+        assert!(!description.to_string().contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_the_error_budget_is_spent() {
+        let (sender, receiver) = unbounded();
+
+        let rustlink = RustlinkBuilder::default()
+            .with_fetch_interval_seconds(1)
+            .with_contract("ETH", "0x9ef1B8c0E4F7dc8bF5719Ea496883DC6401d5b2e")
+            .with_emit_on_start(true)
+            .with_error_budget(ErrorBudget { max_failed_passes: 1, cooldown: None })
+            .build("http://127.0.0.1:1", Reflector::Events(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        let event = receiver.recv().await.unwrap();
+        assert!(matches!(event, FetchEvent::GivingUp { failed_passes: 1 }));
+        assert!(matches!(receiver.recv().await.unwrap(), FetchEvent::Closed));
+        rustlink.shutdown_recv.recv().await.unwrap();
+        assert!(!rustlink.is_running());
+    }
+
+    #[tokio::test]
+    async fn first_fetch_is_delayed_by_the_phase_offset() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_emit_on_start(true)
+            .with_phase_offset(Duration::from_millis(300))
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        rustlink.start().unwrap();
+        receiver.recv().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn unchanged_feeds_are_not_fetched_again() {
+        let (provider, mock) = Provider::mocked();
+        let latest_timestamp = |timestamp: u64| Bytes::from(encode(&[Token::Uint(timestamp.into())]));
+        // Responses are popped last in first out: updated, unchanged, then the first pass
+        mock_round(&mock, 8, 243_200_000_000);
+        mock.push::<Bytes, _>(latest_timestamp(1_700_000_060)).unwrap();
+        mock.push::<Bytes, _>(latest_timestamp(1_700_000_000)).unwrap();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        mock.push::<Bytes, _>(latest_timestamp(1_700_000_000)).unwrap();
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_emit_on_start(true)
+            .with_conditional_fetch(true)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        assert_eq!(receiver.recv().await.unwrap().round_id, 7);
+        // The second pass only reads the unchanged timestamp
+        assert_eq!(receiver.recv().await.unwrap().round_id, 8);
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn frozen_feeds_are_reported_once() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        // The mocked rounds were updated two hours before the clock
+        let clock = Arc::new(MockClock::new((1_700_000_000 + 7_200) * 1000));
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_emit_on_start(true)
+            .with_freeze_threshold(Duration::from_secs(3600))
+            .with_clock(clock)
+            .build_with_provider(provider, Reflector::Events(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        match receiver.recv().await.unwrap() {
+            FetchEvent::FeedFrozen { identifier, last_update, since } => {
+                assert_eq!((identifier.as_str(), last_update), ("ETH", 1_700_000_000));
+                assert_eq!(since, Duration::from_secs(7_200));
+            }
+            event => panic!("unexpected event {:?}", event),
+        }
+        assert!(matches!(receiver.recv().await.unwrap(), FetchEvent::Round(_)));
+        // The same stalled round is not reported again
+        assert!(matches!(receiver.recv().await.unwrap(), FetchEvent::Round(_)));
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn quotes_are_taken_from_the_description() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_emit_on_start(true)
+            .with_quotes_from_description(true)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        assert_eq!(receiver.recv().await.unwrap().quote.as_deref(), Some("USD"));
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn first_fetch_is_aligned_to_the_interval() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        // 700 milliseconds before the next whole second
+        let clock = Arc::new(MockClock::new(1_700_000_000_300));
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_fetch_interval_seconds(1)
+            .with_emit_on_start(true)
+            .with_align_to_interval(true)
+            .with_clock(clock)
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        rustlink.start().unwrap();
+        receiver.recv().await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(700));
+        rustlink.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn feed_status_is_stamped_by_the_configured_clock() {
+        let (provider, mock) = Provider::mocked();
+        mock_round(&mock, 7, 243_155_000_000);
+        mock_contract(&mock);
+        let clock = Arc::new(MockClock::new(1_700_000_000_000));
+        let (sender, receiver) = unbounded();
+
+        let rustlink = eth_feed()
+            .with_emit_on_start(true)
+            .with_clock(clock.clone())
+            .build_with_provider(provider, Reflector::Sender(sender))
+            .unwrap();
+
+        rustlink.start().unwrap();
+        receiver.recv().await.unwrap();
+        rustlink.stop().await.unwrap();
+        assert_eq!(rustlink.feed_status()[0].last_success, Some(1_700_000_000_000));
+
+        clock.advance(Duration::from_secs(1));
+        rustlink.record_fetch(&(0, "ETH".to_string()), false);
+        assert_eq!(rustlink.feed_status()[0].last_error, Some(1_700_000_001_000));
+    }
 }
@@ -0,0 +1,15 @@
+//! Optional log output for applications that do not set up logging themselves.
+
+use tracing_subscriber::{util::TryInitError, EnvFilter};
+
+/// Installs a `tracing-subscriber` formatter printing to stderr, as the global subscriber.
+/// The verbosity is read from the `RUST_LOG` environment variable, e.g. `RUST_LOG=rustlink=debug`,
+/// defaulting to `info`. Log records of `rustlink` and of any other crate are forwarded to it.
+///
+/// Fails if a global subscriber or logger was already installed.
+pub fn init() -> Result<(), TryInitError> {
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    tracing_subscriber::fmt().with_env_filter(filter).finish().try_init()
+}
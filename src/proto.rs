@@ -0,0 +1,134 @@
+//! Protobuf representation of rounds, matching `proto/round.proto`.
+
+use ethers::types::{I256, U256};
+
+use crate::core::Round;
+use crate::error::Error;
+
+/// The `rustlink.Round` message of `proto/round.proto`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RoundProto {
+    #[prost(string, tag = "1")]
+    pub identifier: String,
+    #[prost(uint64, tag = "2")]
+    pub chain_id: u64,
+    #[prost(string, tag = "3")]
+    pub round_id: String,
+    #[prost(string, tag = "4")]
+    pub answered_in_round: String,
+    #[prost(string, tag = "5")]
+    pub started_at: String,
+    #[prost(string, tag = "6")]
+    pub updated_at: String,
+    #[prost(double, tag = "7")]
+    pub answer: f64,
+    #[prost(string, tag = "8")]
+    pub raw_answer: String,
+    #[prost(uint32, tag = "9")]
+    pub decimals: u32,
+    #[prost(double, optional, tag = "10")]
+    pub smoothed_answer: Option<f64>,
+    #[prost(uint64, tag = "11")]
+    pub seq: u64,
+    #[prost(double, optional, tag = "12")]
+    pub velocity: Option<f64>,
+    #[prost(string, optional, tag = "13")]
+    pub exact_answer: Option<String>,
+    #[prost(double, optional, tag = "14")]
+    pub previous_answer: Option<f64>,
+    #[prost(string, optional, tag = "15")]
+    pub previous_round_id: Option<String>,
+    #[prost(string, optional, tag = "16")]
+    pub quote: Option<String>,
+    #[prost(string, tag = "17")]
+    pub description: String,
+}
+
+impl From<Round> for RoundProto {
+    fn from(round: Round) -> Self {
+        RoundProto {
+            identifier: round.identifier,
+            chain_id: round.chain_id,
+            round_id: round.round_id.to_string(),
+            answered_in_round: round.answered_in_round.to_string(),
+            started_at: round.started_at.to_string(),
+            updated_at: round.updated_at.to_string(),
+            answer: round.answer,
+            raw_answer: round.raw_answer.to_string(),
+            decimals: round.decimals.into(),
+            smoothed_answer: round.smoothed_answer,
+            seq: round.seq,
+            velocity: round.velocity,
+            exact_answer: round.exact_answer,
+            previous_answer: round.previous_answer,
+            previous_round_id: round.previous_round_id.map(|round_id| round_id.to_string()),
+            quote: round.quote,
+            description: round.description,
+        }
+    }
+}
+
+impl TryFrom<RoundProto> for Round {
+    type Error = Error;
+
+    /// Fails with `Error::Deserialize` when a numeric string is malformed or out of range.
+    fn try_from(proto: RoundProto) -> Result<Self, Error> {
+        Ok(Round {
+            identifier: proto.identifier,
+            chain_id: proto.chain_id,
+            round_id: proto.round_id.parse().map_err(|_| Error::Deserialize)?,
+            answered_in_round: proto.answered_in_round.parse().map_err(|_| Error::Deserialize)?,
+            started_at: U256::from_dec_str(&proto.started_at).map_err(|_| Error::Deserialize)?,
+            updated_at: U256::from_dec_str(&proto.updated_at).map_err(|_| Error::Deserialize)?,
+            answer: proto.answer,
+            raw_answer: I256::from_dec_str(&proto.raw_answer).map_err(|_| Error::Deserialize)?,
+            decimals: proto.decimals.try_into().map_err(|_| Error::Deserialize)?,
+            smoothed_answer: proto.smoothed_answer,
+            seq: proto.seq,
+            velocity: proto.velocity,
+            exact_answer: proto.exact_answer,
+            previous_answer: proto.previous_answer,
+            previous_round_id: proto
+                .previous_round_id
+                .map(|round_id| round_id.parse().map_err(|_| Error::Deserialize))
+                .transpose()?,
+            quote: proto.quote,
+            description: proto.description,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{I256, U256};
+    use prost::Message;
+
+    use super::RoundProto;
+    use crate::core::Round;
+
+    #[test]
+    fn round_trip() {
+        let round = Round {
+            identifier: "ETH".to_string(),
+            chain_id: 56,
+            round_id: (2u128 << 64) | 10_685,
+            answered_in_round: (2u128 << 64) | 10_685,
+            updated_at: U256::from(1_700_000_000u64),
+            answer: -2431.55,
+            raw_answer: I256::from(-243_155_000_000i64),
+            decimals: 8,
+            velocity: Some(0.5),
+            ..Default::default()
+        };
+
+        let bytes = RoundProto::from(round.clone()).encode_to_vec();
+        let decoded = Round::try_from(RoundProto::decode(bytes.as_slice()).unwrap()).unwrap();
+        assert_eq!(decoded.round_id, round.round_id);
+        assert_eq!(decoded.raw_answer, round.raw_answer);
+        assert_eq!(decoded.updated_at, round.updated_at);
+        assert_eq!(decoded.velocity, Some(0.5));
+
+        let malformed = RoundProto { raw_answer: "2431.55".to_string(), ..RoundProto::from(round) };
+        assert!(Round::try_from(malformed).is_err());
+    }
+}
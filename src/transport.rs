@@ -0,0 +1,103 @@
+//! The HTTP transport of the providers rustlink builds from RPC urls.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ethers::providers::{HttpClientError, JsonRpcClient, JsonRpcError};
+use reqwest::{Client, Url};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Name of the header carrying the JSON-RPC id of a request, when request ids are enabled.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// A JSON-RPC client over HTTP, like `ethers::providers::Http`, that can tag every request with
+/// an `X-Request-Id` header holding its JSON-RPC id. The ids increment per transport, so that a
+/// request can be found in the logs of the RPC provider and correlated with those of rustlink.
+#[derive(Clone, Debug)]
+pub struct RpcHttp {
+    id: Arc<AtomicU64>,
+    client: Client,
+    url: Url,
+    request_ids: bool,
+}
+
+#[derive(Serialize)]
+struct Request<'a, T> {
+    id: u64,
+    jsonrpc: &'a str,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Response {
+    Success { result: serde_json::Value },
+    Error { error: JsonRpcError },
+}
+
+impl RpcHttp {
+    /// Creates a transport sending its requests to `url` through `client`, with an
+    /// `X-Request-Id` header when `request_ids` is set.
+    pub fn new(url: Url, client: Client, request_ids: bool) -> Self {
+        RpcHttp {
+            id: Arc::new(AtomicU64::new(1)),
+            client,
+            url,
+            request_ids,
+        }
+    }
+
+    /// Parses `rpc_url` into a transport, see `new`.
+    pub fn parse(rpc_url: &str, client: Client, request_ids: bool) -> Result<Self, Error> {
+        let url = Url::parse(rpc_url).map_err(|error| Error::InvalidRpcUrl(format!("{}: {}", rpc_url, error)))?;
+        Ok(Self::new(url, client, request_ids))
+    }
+
+    /// Creates a transport to another RPC, sharing the HTTP client and settings of this one.
+    pub fn with_url(&self, rpc_url: &str) -> Result<Self, Error> {
+        Self::parse(rpc_url, self.client.clone(), self.request_ids)
+    }
+
+    /// The url requests are sent to.
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl JsonRpcClient for RpcHttp {
+    type Error = HttpClientError;
+
+    async fn request<T: Debug + Serialize + Send + Sync, R: DeserializeOwned + Send>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, HttpClientError> {
+        let id = self.id.fetch_add(1, Ordering::SeqCst);
+        let mut request = self.client.post(self.url.clone()).json(&Request {
+            id,
+            jsonrpc: "2.0",
+            method,
+            params,
+        });
+        if self.request_ids {
+            request = request.header(REQUEST_ID_HEADER, id);
+        }
+        let body = request.send().await?.bytes().await?;
+
+        let serde_error = |err| HttpClientError::SerdeJson {
+            err,
+            text: String::from_utf8_lossy(&body).to_string(),
+        };
+        match serde_json::from_slice(&body).map_err(serde_error)? {
+            Response::Success { result } => serde_json::from_value(result).map_err(serde_error),
+            Response::Error { error } => Err(error.into()),
+        }
+    }
+}